@@ -0,0 +1,237 @@
+//! Run the Gem transforms over files and directories without a bundler
+//!
+//! ```sh
+//! gem-swc src --config swc-plugin-gem.json --out-dir dist
+//! gem-swc src --config '{"autoImport":true}' --out-dir dist --check
+//! gem-swc src/app.ts --config '{"autoImport":true}'
+//! ```
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use swc_plugin_gem::*;
+
+const HELP: &str = "\
+Usage: gem-swc [OPTIONS] <INPUT>...
+
+Run the Gem transforms over TS/JS files and directories
+
+Options:
+  -c, --config <JSON|FILE>  swc-plugin-gem config, inline JSON or a JSON file
+  -o, --out-dir <DIR>       Write transformed files and source maps to <DIR>
+      --check               Don't write, fail when the files in <DIR> would change
+      --no-source-maps      Don't write source maps
+  -h, --help                Print help
+
+Without --out-dir a single input file is printed to stdout, --check
+requires --out-dir.
+";
+
+const EXTENSIONS: [&str; 8] = ["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+#[derive(Default)]
+struct Args {
+    inputs: Vec<PathBuf>,
+    config: Option<String>,
+    out_dir: Option<PathBuf>,
+    check: bool,
+    source_maps: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        source_maps: true,
+        ..Default::default()
+    };
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| {
+            iter.next()
+                .ok_or_else(|| format!("missing value for `{name}`"))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print!("{HELP}");
+                std::process::exit(0);
+            }
+            "-c" | "--config" => args.config = Some(value(&arg)?),
+            "-o" | "--out-dir" => args.out_dir = Some(value(&arg)?.into()),
+            "--check" => args.check = true,
+            "--no-source-maps" => args.source_maps = false,
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ => args.inputs.push(arg.into()),
+        }
+    }
+    if args.inputs.is_empty() {
+        return Err("missing <INPUT>".into());
+    }
+    if args.check && args.out_dir.is_none() {
+        return Err("`--check` requires `--out-dir`".into());
+    }
+    Ok(args)
+}
+
+fn read_config(config: Option<&str>) -> Result<PluginConfig, Vec<String>> {
+    let json = match config {
+        None => "{}".to_string(),
+        Some(inline) if inline.trim_start().starts_with('{') => inline.to_string(),
+        Some(file) => fs::read_to_string(file)
+            .map_err(|err| vec![format!("failed to read config `{file}`: {err}")])?,
+    };
+    let (config, warnings) = parse_config(&json)?;
+    warnings.iter().for_each(|msg| eprintln!("warning: {msg}"));
+    Ok(config)
+}
+
+/// (input file, path relative to the input root)
+fn collect_files(input: &Path, root: &Path, files: &mut Vec<(PathBuf, PathBuf)>) {
+    if input.is_dir() {
+        let Ok(entries) = fs::read_dir(input) else {
+            return;
+        };
+        let mut entries: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        entries.sort();
+        for path in entries {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if name.starts_with('.') || name == "node_modules" {
+                continue;
+            }
+            if path.is_dir() || is_source_file(&path) {
+                collect_files(&path, root, files);
+            }
+        }
+    } else {
+        let relative = input
+            .strip_prefix(root)
+            .ok()
+            .filter(|x| !x.as_os_str().is_empty())
+            .or(input.file_name().map(Path::new))
+            .unwrap_or(input);
+        files.push((input.to_path_buf(), relative.to_path_buf()));
+    }
+}
+
+fn is_source_file(path: &Path) -> bool {
+    let name = path.to_string_lossy();
+    let ext = path.extension().unwrap_or_default().to_string_lossy();
+    EXTENSIONS.contains(&ext.as_ref()) && !name.ends_with(".d.ts")
+}
+
+/// `None` when the file has errors
+fn transform_file(config: &PluginConfig, path: &Path) -> Option<TransformOutput> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("error: failed to read `{}`: {err}", path.display());
+            return None;
+        }
+    };
+    match transform(source, &path.to_string_lossy(), config) {
+        Ok(output) => {
+            output
+                .warnings
+                .iter()
+                .for_each(|msg| eprintln!("warning: {msg}"));
+            Some(output)
+        }
+        Err(errors) => {
+            errors.iter().for_each(|msg| eprintln!("error: {msg}"));
+            None
+        }
+    }
+}
+
+fn run(args: Args) -> Result<bool, String> {
+    let config = read_config(args.config.as_deref()).map_err(|errors| errors.join("\n"))?;
+
+    let mut files = vec![];
+    for input in &args.inputs {
+        if !input.exists() {
+            return Err(format!("input `{}` does not exist", input.display()));
+        }
+        collect_files(input, input, &mut files);
+    }
+
+    if args.out_dir.is_none() && (files.len() != 1 || args.inputs[0].is_dir()) {
+        return Err("`--out-dir` is required for directories and multiple files".into());
+    }
+
+    let mut changed = vec![];
+    let mut ok = true;
+    for (path, relative) in files {
+        let Some(TransformOutput {
+            code, map, report, ..
+        }) = transform_file(&config, &path)
+        else {
+            ok = false;
+            continue;
+        };
+
+        let Some(out_dir) = &args.out_dir else {
+            print!("{code}");
+            if let Some(report_path) = config.report.path(&path) {
+                write_report(&report, &report_path)?;
+            }
+            continue;
+        };
+
+        let out_file = out_dir.join(&relative);
+        let map_file = PathBuf::from(format!("{}.map", out_file.display()));
+        let code = if args.source_maps {
+            format!(
+                "{code}\n//# sourceMappingURL={}\n",
+                map_file.file_name().unwrap_or_default().to_string_lossy()
+            )
+        } else {
+            code
+        };
+
+        if args.check {
+            if fs::read_to_string(&out_file).ok().as_deref() != Some(code.as_str()) {
+                changed.push(out_file);
+            }
+            continue;
+        }
+
+        if let Some(parent) = out_file.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        fs::write(&out_file, code).map_err(|err| err.to_string())?;
+        if args.source_maps {
+            fs::write(&map_file, map).map_err(|err| err.to_string())?;
+        }
+        if let Some(report_path) = config.report.path(&out_file) {
+            write_report(&report, &report_path)?;
+        }
+    }
+
+    for path in &changed {
+        eprintln!("would change: {}", path.display());
+    }
+
+    Ok(ok && changed.is_empty())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{HELP}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = run(args);
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+const CONFIG: &str = r#"{"autoImport":true}"#;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("gem-swc-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(
+        dir.join("src/app.ts"),
+        "@customElement('my-app')\nexport class App extends GemElement {}\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn gem_swc(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_gem-swc"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn should_write_out_dir_with_source_maps() {
+    let dir = temp_dir("out-dir");
+    let (src, dist) = (dir.join("src"), dir.join("dist"));
+    let output = gem_swc(&[
+        src.to_str().unwrap(),
+        "--config",
+        CONFIG,
+        "--out-dir",
+        dist.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{output:?}");
+
+    let code = fs::read_to_string(dist.join("app.ts")).unwrap();
+    let map = fs::read_to_string(dist.join("app.ts.map")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(code.contains("import { customElement, GemElement } from \"@mantou/gem\""));
+    assert!(code.ends_with("//# sourceMappingURL=app.ts.map\n"));
+    assert!(map.contains("\"mappings\""));
+}
+
+#[test]
+fn should_check_out_dir() {
+    let dir = temp_dir("check");
+    let (src, dist) = (dir.join("src"), dir.join("dist"));
+    let args = [
+        src.to_str().unwrap(),
+        "--config",
+        CONFIG,
+        "--out-dir",
+        dist.to_str().unwrap(),
+    ];
+    let check = |args: &[&str]| gem_swc(&[args, &["--check"]].concat());
+
+    let stale = check(&args);
+    gem_swc(&args);
+    let fresh = check(&args);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(!stale.status.success());
+    assert!(String::from_utf8_lossy(&stale.stderr).contains("would change"));
+    assert!(fresh.status.success(), "{fresh:?}");
+}
+
+#[test]
+fn should_reject_check_without_out_dir() {
+    let dir = temp_dir("check-stdout");
+    let output = gem_swc(&[dir.join("src/app.ts").to_str().unwrap(), "--check"]);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("`--check` requires `--out-dir`"));
+}
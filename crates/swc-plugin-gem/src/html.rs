@@ -0,0 +1,218 @@
+//! 扫描模板中的元素开始标签
+//!
+//! 模板被表达式分成多段，扫描状态跨段保留，表达式只会结束标签名，
+//! 注释、属性值和 `<script>` / `<style>` 等中的内容会被忽略
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static CUSTOM_ELEMENT_NAME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\w+(-\w+)+$").unwrap());
+
+/// https://html.spec.whatwg.org/multipage/custom-elements.html#valid-custom-element-name
+const RESERVED_NAMES: [&str; 8] = [
+    "annotation-xml",
+    "color-profile",
+    "font-face",
+    "font-face-src",
+    "font-face-uri",
+    "font-face-format",
+    "font-face-name",
+    "missing-glyph",
+];
+
+/// 内容不是 HTML 的元素
+const RAW_TEXT_ELEMENTS: [&str; 5] = ["script", "style", "textarea", "title", "xmp"];
+
+pub fn is_custom_element_name(name: &str) -> bool {
+    CUSTOM_ELEMENT_NAME_REGEX.is_match(name) && !RESERVED_NAMES.contains(&name)
+}
+
+#[derive(Clone, PartialEq, Default)]
+enum State {
+    #[default]
+    Text,
+    /// `<!--`
+    Comment,
+    /// `<!DOCTYPE>`, `<?xml>`
+    Bogus,
+    /// `start`: 标签名所在的段和段中的位置
+    TagName {
+        name: String,
+        closing: bool,
+        start: (usize, usize),
+    },
+    /// 开始标签中，`raw_text`：结束后进入原始文本
+    Tag {
+        raw_text: Option<String>,
+    },
+    AttrValue {
+        quote: char,
+        raw_text: Option<String>,
+    },
+    RawText(String),
+}
+
+/// 模板中的元素开始标签
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScannedTag {
+    pub name: String,
+    /// 所在的段
+    pub quasi: usize,
+    /// 标签名在段中的位置
+    pub offset: usize,
+}
+
+#[derive(Default)]
+struct Scanner {
+    state: State,
+    /// 当前的段
+    quasi: usize,
+    tags: Vec<ScannedTag>,
+}
+
+impl Scanner {
+    fn end_tag_name(&mut self) {
+        let State::TagName {
+            name,
+            closing,
+            start: (quasi, offset),
+        } = &self.state
+        else {
+            return;
+        };
+        let (name, closing) = (name.to_ascii_lowercase(), *closing);
+        if !closing && is_custom_element_name(&name) {
+            self.tags.push(ScannedTag {
+                name: name.clone(),
+                quasi: *quasi,
+                offset: *offset,
+            });
+        }
+        let raw_text = (!closing && RAW_TEXT_ELEMENTS.contains(&name.as_str())).then_some(name);
+        self.state = State::Tag { raw_text };
+    }
+
+    fn scan(&mut self, text: &str) {
+        let mut index = 0;
+        while let Some(c) = text[index..].chars().next() {
+            let rest = &text[index..];
+            let mut len = c.len_utf8();
+            match self.state.clone() {
+                State::Text => {
+                    if rest.starts_with("<!--") {
+                        self.state = State::Comment;
+                        len = 4;
+                    } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                        self.state = State::Bogus;
+                    } else if let Some(name) = rest.strip_prefix("</") {
+                        if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                            self.state = State::TagName {
+                                name: String::new(),
+                                closing: true,
+                                start: (self.quasi, index + 2),
+                            };
+                            len = 2;
+                        }
+                    } else if c == '<' && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                        self.state = State::TagName {
+                            name: String::new(),
+                            closing: false,
+                            start: (self.quasi, index + 1),
+                        };
+                    }
+                }
+                State::Comment => {
+                    if rest.starts_with("-->") {
+                        self.state = State::Text;
+                        len = 3;
+                    }
+                }
+                State::Bogus => {
+                    if c == '>' {
+                        self.state = State::Text;
+                    }
+                }
+                State::TagName {
+                    mut name,
+                    closing,
+                    start,
+                } => {
+                    if c.is_whitespace() || c == '/' || c == '>' {
+                        self.end_tag_name();
+                        // `>` 在 `Tag` 中处理
+                        continue;
+                    }
+                    name.push(c);
+                    self.state = State::TagName {
+                        name,
+                        closing,
+                        start,
+                    };
+                }
+                State::Tag { raw_text } => match c {
+                    '>' => {
+                        self.state = raw_text.map_or(State::Text, State::RawText);
+                    }
+                    '"' | '\'' => {
+                        self.state = State::AttrValue { quote: c, raw_text };
+                    }
+                    _ => {}
+                },
+                State::AttrValue { quote, raw_text } => {
+                    if c == quote {
+                        self.state = State::Tag { raw_text };
+                    }
+                }
+                State::RawText(name) => {
+                    let end = format!("</{name}");
+                    if rest.len() >= end.len()
+                        && rest.is_char_boundary(end.len())
+                        && rest[..end.len()].eq_ignore_ascii_case(&end)
+                    {
+                        self.state = State::TagName {
+                            name,
+                            closing: true,
+                            start: (self.quasi, index + 2),
+                        };
+                        len = end.len();
+                    }
+                }
+            }
+            index += len;
+        }
+    }
+}
+
+/// `quasis`: 模板字符串各段的原始文本，返回自定义元素标签（有重复）
+pub fn scan_custom_elements<'a>(quasis: impl IntoIterator<Item = &'a str>) -> Vec<ScannedTag> {
+    let mut scanner = Scanner::default();
+    for (index, quasi) in quasis.into_iter().enumerate() {
+        // 表达式结束标签名，例如 `<dy-use${attrs}>`
+        scanner.end_tag_name();
+        scanner.quasi = index;
+        scanner.scan(quasi);
+    }
+    scanner.end_tag_name();
+    scanner.tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_scan_custom_elements() {
+        let tags = scan_custom_elements([
+            "<dy-use .x=",
+            "></dy-use><!-- <dy-comment> ",
+            " --><div title=\"<dy-attr>\" class='",
+            "'><dy-split",
+            " a></dy-split><style>dy-x { } <dy-style></style><font-face></font-face><DY-Upper>",
+        ]);
+        assert_eq!(
+            tags.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(),
+            vec!["dy-use", "dy-split", "dy-upper"]
+        );
+        assert_eq!((tags[1].quasi, tags[1].offset), (3, 3));
+    }
+}
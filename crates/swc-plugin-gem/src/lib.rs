@@ -1,35 +1,64 @@
+use std::path::Path;
+
+pub use report::{
+    collect_report, write_report, HmrClass, HmrStatus, ImportedElement, ImportedMember, Preload,
+    ReportConfig, TransformReport,
+};
 use serde::Deserialize;
-use swc_common::pass::Optional;
+use serde_json::{Map, Value};
+use swc_common::{errors::HANDLER, Mark};
 use swc_core::{
-    ecma::visit::VisitMutWith,
+    ecma::visit::{VisitMut, VisitMutWith},
     plugin::{
         metadata::TransformPluginMetadataContextKind, plugin_transform,
         proxies::TransformPluginProgramMetadata,
     },
 };
 use swc_ecma_ast::Program;
+#[cfg(feature = "native")]
+pub use transform::{transform, TransformOutput};
+use visitors::{
+    hmr::hmr_hook,
+    hook::{Hook, HookVisitor},
+    import::import_hook,
+    memo::memo_hook,
+    minify::minify_hook,
+    path::path_hook,
+    preload::preload_hook,
+    selector::selector_hook,
+};
 pub use visitors::{
     hmr::{hmr_transform, HmrConfig, HmrTarget},
-    import::{import_transform, AutoImport, AutoImportContent, AutoImportDts, MemberOrMemberAs},
+    import::{
+        import_transform, AutoImport, AutoImportContent, AutoImportDts, AutoImportExtends,
+        MemberOrMemberAs,
+    },
     memo::memo_transform,
     minify::minify_transform,
-    path::path_transform,
+    path::{import_map_parts_dir, path_transform, ImportMap, ResolvePath, ResolvePathOptions},
     preload::preload_transform,
     selector::selector_transform,
 };
 
+mod html;
+mod registry;
+mod report;
+mod resolve;
+mod suggest;
+#[cfg(feature = "native")]
+mod transform;
 mod visitors;
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 #[serde(default, rename_all = "camelCase")]
-struct PluginConfig {
+pub struct PluginConfig {
     pub style_minify: bool,
     /// e.g: https://github.com/mantou132/gem/blob/main/crates/swc-plugin-gem/README.md#example
     pub auto_import: AutoImport,
     /// Generate .d.ts file, use src/auto-import.d.ts when true
     pub auto_import_dts: AutoImportDts,
-    /// Use esm directly with import map
-    pub resolve_path: bool,
+    /// Use esm directly with import map, `true` or `ResolvePathOptions`
+    pub resolve_path: ResolvePath,
     ///depend on URL loader & top await
     pub preload: bool,
     /// Under development. `true` / `"webpack-hot"` emit `import.meta.webpackHot`;
@@ -38,55 +67,155 @@ struct PluginConfig {
     pub hmr: HmrConfig,
     /// Support `&:hover` in shadow dom and light dom
     pub selector_compatible: bool,
-    /// un-implement
+    /// Depend on `autoImport`, route elements are loaded with `import()` when first rendered
     pub lazy_view: bool,
+    /// Write what the plugin did to each file as JSON, `true` or output directory
+    pub report: ReportConfig,
+}
+
+const CONFIG_KEYS: [&str; 9] = [
+    "styleMinify",
+    "autoImport",
+    "autoImportDts",
+    "resolvePath",
+    "preload",
+    "hmr",
+    "selectorCompatible",
+    "lazyView",
+    "report",
+];
+
+/// Parse plugin config JSON, Ok: config and warnings, Err: errors
+pub fn parse_config(config: &str) -> Result<(PluginConfig, Vec<String>), Vec<String>> {
+    let allowed_keys = || {
+        CONFIG_KEYS
+            .iter()
+            .map(|key| format!("`{key}`"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let object = match serde_json::from_str::<Value>(config) {
+        Ok(Value::Object(object)) => object,
+        Ok(_) => {
+            return Err(vec![format!(
+                "gem plugin config must be an object, allowed keys: {}",
+                allowed_keys()
+            )])
+        }
+        Err(err) => return Err(vec![format!("invalid config for gem plugin: {err}")]),
+    };
+
+    let mut warnings = vec![];
+    let mut errors = vec![];
+    for (key, value) in &object {
+        if !CONFIG_KEYS.contains(&key.as_str()) {
+            warnings.push(format!(
+                "unknown key `{key}` in gem plugin config, allowed keys: {}",
+                allowed_keys()
+            ));
+            continue;
+        }
+        let single = Value::Object(Map::from_iter([(key.clone(), value.clone())]));
+        if let Err(err) = serde_json::from_value::<PluginConfig>(single) {
+            errors.push(format!(
+                "invalid value for `{key}` in gem plugin config: {err}"
+            ));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    serde_json::from_value::<PluginConfig>(Value::Object(object))
+        .map(|config| (config, warnings))
+        .map_err(|err| vec![format!("invalid config for gem plugin: {err}")])
+}
+
+/// All passes in plugin order, `filename` is used by `resolvePath` and `hmr`,
+/// `unresolved_mark` is the mark passed to `resolver`.
+/// Enabled passes share one traversal
+pub fn gem_transform(
+    config: &PluginConfig,
+    filename: Option<String>,
+    unresolved_mark: Mark,
+) -> impl VisitMut {
+    let hmr_target = config.hmr.target();
+    let auto_import = match config.auto_import {
+        AutoImport::Gem(enabled) => enabled,
+        AutoImport::CustomContent(_) => true,
+    };
+
+    let mut hooks: Vec<Box<dyn Hook>> = vec![];
+    // 只支持原生装饰器或 `runPluginFirst`，不然被转译了，改写不了
+    hooks.push(Box::new(memo_hook()));
+    if auto_import {
+        hooks.push(Box::new(import_hook(
+            config.auto_import.clone(),
+            config.auto_import_dts.clone(),
+            config.lazy_view,
+            unresolved_mark,
+            filename.clone(),
+            config
+                .resolve_path
+                .options()
+                .and_then(|options| options.extensions),
+        )));
+    }
+    if config.selector_compatible {
+        hooks.push(Box::new(selector_hook()));
+    }
+    if config.style_minify {
+        hooks.push(Box::new(minify_hook()));
+    }
+    if let Some(options) = config.resolve_path.options() {
+        hooks.push(Box::new(path_hook(filename.clone(), options)));
+    }
+    if config.preload {
+        hooks.push(Box::new(preload_hook()));
+    }
+    if let Some(target) = hmr_target {
+        hooks.push(Box::new(hmr_hook(filename, target)));
+    }
+
+    HookVisitor::new(hooks)
 }
 
 #[plugin_transform]
 pub fn process_transform(mut program: Program, data: TransformPluginProgramMetadata) -> Program {
-    let plugin_config = &data
+    let plugin_config = data
         .get_transform_plugin_config()
-        .expect("failed to get plugin config for gem plugin");
-    let config =
-        serde_json::from_str::<PluginConfig>(plugin_config).expect("invalid config for gem plugin");
+        .unwrap_or_else(|| "{}".into());
+    let config = match parse_config(&plugin_config) {
+        Ok((config, warnings)) => {
+            HANDLER.with(|handler| warnings.iter().for_each(|msg| handler.warn(msg)));
+            config
+        }
+        Err(errors) => {
+            HANDLER.with(|handler| errors.iter().for_each(|msg| handler.err(msg)));
+            return program;
+        }
+    };
 
     let filename = data.get_context(&TransformPluginMetadataContextKind::Filename);
-    let hmr_target = config.hmr.target();
+    let report_path = filename
+        .as_ref()
+        .and_then(|filename| config.report.path(Path::new(filename)));
+    let Some(report_path) = report_path else {
+        program.visit_mut_with(&mut gem_transform(&config, filename, data.unresolved_mark));
+        return program;
+    };
 
-    program.visit_mut_with(&mut (
-        Optional {
-            // 只支持原生装饰器或 `runPluginFirst`，不然被转译了，改写不了
-            enabled: true,
-            visitor: memo_transform(),
-        },
-        Optional {
-            enabled: match config.auto_import {
-                AutoImport::Gem(enabled) => enabled,
-                AutoImport::CustomContent(_) => true,
-            },
-            visitor: import_transform(config.auto_import, config.auto_import_dts),
-        },
-        Optional {
-            enabled: config.selector_compatible,
-            visitor: selector_transform(),
-        },
-        Optional {
-            enabled: config.style_minify,
-            visitor: minify_transform(),
-        },
-        Optional {
-            enabled: config.resolve_path,
-            visitor: path_transform(filename.clone()),
-        },
-        Optional {
-            enabled: config.preload,
-            visitor: preload_transform(),
-        },
-        Optional {
-            enabled: hmr_target.is_some(),
-            visitor: hmr_transform(filename.clone(), hmr_target.unwrap_or_default()),
-        },
-    ));
+    let (_, report) = collect_report(filename.as_deref().unwrap_or_default(), || {
+        program.visit_mut_with(&mut gem_transform(
+            &config,
+            filename.clone(),
+            data.unresolved_mark,
+        ))
+    });
+    if let Err(err) = write_report(&report, &report_path) {
+        HANDLER.with(|handler| handler.warn(&err));
+    }
 
     program
 }
@@ -107,9 +236,91 @@ mod tests {
         )
     }
 
+    #[test]
+    fn should_report_unknown_config_key() {
+        let (config, warnings) = parse_config(r#"{"styleMinify":true,"lazy":true}"#).unwrap();
+        assert!(config.style_minify);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("unknown key `lazy` in gem plugin config"));
+        assert!(warnings[0].contains("`lazyView`"));
+    }
+
+    #[test]
+    fn should_report_invalid_config_value() {
+        let errors = parse_config(r#"{"preload":true,"hmr":"hot","styleMinify":1}"#).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("invalid value for `hmr`"));
+        assert!(errors[1].starts_with("invalid value for `styleMinify`"));
+    }
+
     #[test]
     fn should_parse_hmr_target() {
         let config = serde_json::from_str::<PluginConfig>(r#"{"hmr":"import-meta-hot"}"#).unwrap();
         assert_eq!(config.hmr.target(), Some(HmrTarget::ImportMetaHot));
     }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn should_transform_source() {
+        let config = PluginConfig {
+            auto_import: AutoImport::Gem(true),
+            ..Default::default()
+        };
+        let source = "class App extends GemElement {}".to_string();
+        let output = transform(source, "src/app.ts", &config).unwrap();
+        assert!(output
+            .code
+            .starts_with("import { GemElement } from \"@mantou/gem\";\n"));
+        assert!(output.map.contains(r#""sources":["src/app.ts"]"#));
+        assert!(output.warnings.is_empty());
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn should_return_transform_errors() {
+        let config = PluginConfig {
+            preload: true,
+            ..Default::default()
+        };
+        let source = "import { a } from './a.png?preload';".to_string();
+        let errors = transform(source, "app.ts", &config).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("app.ts:1:1: preload only allow one default specifier"));
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn should_collect_report() {
+        let config = PluginConfig {
+            auto_import: AutoImport::Gem(true),
+            hmr: HmrConfig::Enabled(true),
+            ..Default::default()
+        };
+        let source = "@customElement('my-app') class App extends GemElement {}".to_string();
+        let report = transform(source, "app.ts", &config).unwrap().report;
+        assert_eq!(report.filename, "app.ts");
+        assert_eq!(report.imported_members.len(), 2);
+        assert_eq!(
+            report.hmr_classes,
+            vec![HmrClass {
+                name: Some("App".into()),
+                key: "my-app".into(),
+            }]
+        );
+        assert_eq!(report.hmr_status, Some(HmrStatus::Accept));
+    }
+
+    #[test]
+    fn should_return_report_path() {
+        let file = Path::new("src/app.ts");
+        assert_eq!(ReportConfig::Enabled(false).path(file), None);
+        assert_eq!(
+            ReportConfig::Enabled(true).path(file),
+            Some("src/app.ts.gem.json".into())
+        );
+        assert_eq!(
+            ReportConfig::Dir("reports".into()).path(file),
+            Some("reports/src/app.ts.gem.json".into())
+        );
+    }
 }
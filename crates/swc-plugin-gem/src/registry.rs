@@ -0,0 +1,264 @@
+//! 项目中用 `@customElement` 定义的元素
+//!
+//! 扫描目录中的源文件，按文件 mtime 缓存，也可以读取 JSON 注册表；
+//! 整个结果按目录（或 JSON 文件）的 mtime 缓存，目录没有变化时不再遍历
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use swc_common::{sync::Lrc, FileName, SourceMap};
+use swc_core::ecma::{
+    parser::{parse_file_as_module, EsSyntax, Syntax, TsSyntax},
+    visit::{Visit, VisitWith},
+};
+use swc_ecma_ast::{Callee, Class, EsVersion, Lit};
+
+const EXTENSIONS: [&str; 6] = ["ts", "tsx", "mts", "js", "jsx", "mjs"];
+
+/// 根据扩展名，都启用装饰器
+pub fn get_syntax(filename: &str) -> Syntax {
+    let ext = Path::new(filename)
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy();
+    match ext.as_ref() {
+        "ts" | "tsx" | "mts" | "cts" => Syntax::Typescript(TsSyntax {
+            tsx: ext == "tsx",
+            decorators: true,
+            ..Default::default()
+        }),
+        _ => Syntax::Es(EsSyntax {
+            jsx: ext == "jsx",
+            decorators: true,
+            ..Default::default()
+        }),
+    }
+}
+
+/// `@customElement('app-x')` 的标签名
+pub fn custom_element_tag(node: &Class) -> Option<String> {
+    node.decorators.iter().find_map(|decorator| {
+        let call = decorator.expr.as_call()?;
+        let Callee::Expr(callee) = &call.callee else {
+            return None;
+        };
+        if callee.as_ident()?.sym != "customElement" {
+            return None;
+        }
+        match call.args.first()?.expr.as_lit()? {
+            Lit::Str(tag) => Some(tag.value.as_str()?.to_string()),
+            _ => None,
+        }
+    })
+}
+
+#[derive(Default)]
+struct TagCollector {
+    tags: Vec<String>,
+}
+
+impl Visit for TagCollector {
+    fn visit_class(&mut self, node: &Class) {
+        self.tags.extend(custom_element_tag(node));
+        node.visit_children_with(self);
+    }
+}
+
+fn scan_file(path: &Path) -> Vec<String> {
+    let Ok(source) = fs::read_to_string(path) else {
+        return vec![];
+    };
+    // 大部分文件没有定义元素，不用解析
+    if !source.contains("customElement") {
+        return vec![];
+    }
+    let cm: Lrc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Real(path.to_path_buf()).into(), source);
+    let Ok(module) = parse_file_as_module(
+        &fm,
+        get_syntax(&path.to_string_lossy()),
+        EsVersion::latest(),
+        None,
+        &mut vec![],
+    ) else {
+        return vec![];
+    };
+    let mut collector = TagCollector::default();
+    module.visit_with(&mut collector);
+    collector.tags
+}
+
+fn get_mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+/// 同时记录遍历的目录，用于检查缓存
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>, dirs: &mut Vec<PathBuf>) {
+    // 不存在的目录也记录，创建后重新扫描
+    dirs.push(dir.to_path_buf());
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    // `file_type` 一般不需要额外的 `stat`，符号链接除外
+    let mut entries: Vec<(PathBuf, bool)> = entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            let is_dir = entry
+                .file_type()
+                .is_ok_and(|x| x.is_dir() || (x.is_symlink() && path.is_dir()));
+            (path, is_dir)
+        })
+        .collect();
+    entries.sort();
+    for (path, is_dir) in entries {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let ext = path.extension().unwrap_or_default().to_string_lossy();
+        if name.starts_with('.') || name == "node_modules" {
+            continue;
+        }
+        if is_dir {
+            collect_files(&path, files, dirs);
+        } else if EXTENSIONS.contains(&ext.as_ref()) && !name.ends_with(".d.ts") {
+            files.push(path);
+        }
+    }
+}
+
+/// file -> (mtime, tags)
+static SCAN_CACHE: Lazy<Mutex<HashMap<PathBuf, (Option<SystemTime>, Vec<String>)>>> =
+    Lazy::new(Default::default);
+
+/// 目录或者 JSON 注册表 -> (遍历的目录或者 JSON 文件和它们的 mtime, tag -> 文件)
+type CachedRegistry = (
+    Vec<(PathBuf, Option<SystemTime>)>,
+    IndexMap<String, PathBuf>,
+);
+
+static REGISTRY_CACHE: Lazy<Mutex<HashMap<PathBuf, CachedRegistry>>> = Lazy::new(Default::default);
+
+fn scan_dir(dir: &Path, dirs: &mut Vec<PathBuf>) -> IndexMap<String, PathBuf> {
+    let mut files = vec![];
+    collect_files(dir, &mut files, dirs);
+
+    let Ok(mut cache) = SCAN_CACHE.lock() else {
+        return Default::default();
+    };
+    let mut registry = IndexMap::new();
+    for file in files {
+        let mtime = get_mtime(&file);
+        let tags = match cache.get(&file) {
+            Some((cached, tags)) if *cached == mtime => tags.clone(),
+            _ => {
+                let tags = scan_file(&file);
+                cache.insert(file.clone(), (mtime, tags.clone()));
+                tags
+            }
+        };
+        for tag in tags {
+            registry.entry(tag).or_insert_with(|| file.clone());
+        }
+    }
+    registry
+}
+
+/// `{ "app-x": "./elements/x.ts" }`，路径相对注册表文件
+fn read_registry(file: &Path) -> IndexMap<String, PathBuf> {
+    let dir = file.parent().unwrap_or(Path::new(""));
+    fs::read_to_string(file)
+        .ok()
+        .and_then(|content| serde_json::from_str::<IndexMap<String, String>>(&content).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(tag, path)| (tag, dir.join(path)))
+        .collect()
+}
+
+/// `path`: 扫描的目录或者 `.json` 注册表，返回 tag -> 文件
+///
+/// 目录的 mtime 只在添加、删除、重命名文件时改变，修改已有文件的内容在目录变化后才会重新扫描
+pub fn load_registry(path: &Path) -> IndexMap<String, PathBuf> {
+    if let Ok(cache) = REGISTRY_CACHE.lock() {
+        if let Some((stamps, registry)) = cache.get(path) {
+            if stamps.iter().all(|(path, mtime)| &get_mtime(path) == mtime) {
+                return registry.clone();
+            }
+        }
+    }
+
+    let mut watched = vec![];
+    let registry = if path.extension().is_some_and(|ext| ext == "json") {
+        watched.push(path.to_path_buf());
+        read_registry(path)
+    } else {
+        scan_dir(path, &mut watched)
+    };
+    if let Ok(mut cache) = REGISTRY_CACHE.lock() {
+        let stamps = watched
+            .into_iter()
+            .map(|path| {
+                let mtime = get_mtime(&path);
+                (path, mtime)
+            })
+            .collect();
+        cache.insert(path.to_path_buf(), (stamps, registry.clone()));
+    }
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn should_scan_custom_elements() {
+        let dir = env::temp_dir().join(format!("gem-registry-{}", std::process::id()));
+        fs::create_dir_all(dir.join("elements")).unwrap();
+        fs::write(
+            dir.join("elements/button.ts"),
+            "@customElement('app-button')\nexport class AppButton extends GemElement {}",
+        )
+        .unwrap();
+        fs::write(dir.join("elements/button.d.ts"), "@customElement('app-x')").unwrap();
+        fs::write(dir.join("main.ts"), "customElement;").unwrap();
+        fs::write(dir.join("registry.json"), r#"{"app-link":"./link.js"}"#).unwrap();
+
+        let registry = load_registry(&dir);
+        let json_registry = load_registry(&dir.join("registry.json"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            registry.into_iter().collect::<Vec<_>>(),
+            vec![("app-button".to_string(), dir.join("elements/button.ts"))]
+        );
+        assert_eq!(json_registry.get("app-link"), Some(&dir.join("./link.js")));
+    }
+
+    #[test]
+    fn should_rescan_changed_dir() {
+        let dir = env::temp_dir().join(format!("gem-registry-cache-{}", std::process::id()));
+        assert!(load_registry(&dir).is_empty());
+
+        fs::create_dir_all(dir.join("elements")).unwrap();
+        fs::write(
+            dir.join("elements/button.ts"),
+            "@customElement('app-button')\nexport class AppButton extends GemElement {}",
+        )
+        .unwrap();
+        let registry = load_registry(&dir);
+        let cached = load_registry(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(registry.keys().collect::<Vec<_>>(), vec!["app-button"]);
+        assert_eq!(cached, registry);
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::visitors::path::get_cwd;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ReportConfig {
+    /// `true`: `<file>.gem.json` next to the source file
+    Enabled(bool),
+    /// `<dir>/<file>.gem.json`, file is relative to cwd
+    Dir(String),
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        ReportConfig::Enabled(false)
+    }
+}
+
+impl ReportConfig {
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self, ReportConfig::Enabled(false))
+    }
+
+    /// 插件中不知道输出位置，`file` 使用源文件，
+    /// wasm 中只能写 `/cwd` 下的文件
+    pub fn path(&self, file: &Path) -> Option<PathBuf> {
+        let name = |file: &Path| format!("{}.gem.json", file.display());
+        let cwd = get_cwd();
+        let relative = || match file.strip_prefix(&cwd) {
+            Ok(relative) => Some(relative),
+            Err(_) if file.is_relative() => Some(file),
+            Err(_) => Some(Path::new(file.file_name()?)),
+        };
+        match self {
+            ReportConfig::Enabled(false) => None,
+            ReportConfig::Enabled(true) if cfg!(target_family = "wasm") => {
+                Some(cwd.join(name(relative()?)))
+            }
+            ReportConfig::Enabled(true) => Some(name(file).into()),
+            ReportConfig::Dir(dir) => Some(Path::new(dir).join(name(relative()?))),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedMember {
+    pub local: String,
+    pub imported: Option<String>,
+    pub package: String,
+    /// `import type`
+    pub type_only: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedElement {
+    pub tag: String,
+    pub module: String,
+    /// `lazyView`, loaded with `import()`
+    pub lazy: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HmrClass {
+    pub name: Option<String>,
+    pub key: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum HmrStatus {
+    Accept,
+    Decline,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Preload {
+    pub source: String,
+    pub local: String,
+    /// `image` or `arrayBuffer`
+    pub kind: String,
+}
+
+/// What the plugin did to a file
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformReport {
+    pub filename: String,
+    pub imported_members: Vec<ImportedMember>,
+    pub imported_elements: Vec<ImportedElement>,
+    pub hmr_classes: Vec<HmrClass>,
+    pub hmr_status: Option<HmrStatus>,
+    pub preloads: Vec<Preload>,
+    /// Bare specifiers kept for `resolvePath.emitImportMap` and their import map values
+    pub bare_imports: BTreeMap<String, String>,
+}
+
+thread_local! {
+    static REPORT: RefCell<Option<TransformReport>> = const { RefCell::new(None) };
+}
+
+/// Collect the report of the transforms run in `f`
+pub fn collect_report<T>(filename: &str, f: impl FnOnce() -> T) -> (T, TransformReport) {
+    let saved = REPORT.with(|report| {
+        report.replace(Some(TransformReport {
+            filename: filename.to_string(),
+            ..Default::default()
+        }))
+    });
+    let result = f();
+    let report = REPORT.with(|report| report.replace(saved));
+    (result, report.unwrap_or_default())
+}
+
+/// 不在 `collect_report` 中时什么也不做
+pub(crate) fn record(f: impl FnOnce(&mut TransformReport)) {
+    REPORT.with(|report| {
+        if let Some(report) = report.borrow_mut().as_mut() {
+            f(report);
+        }
+    });
+}
+
+pub fn write_report(report: &TransformReport, path: &Path) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(report).map_err(|err| err.to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    fs::write(path, content + "\n")
+        .map_err(|err| format!("failed to write report `{}`: {err}", path.display()))
+}
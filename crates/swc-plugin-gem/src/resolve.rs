@@ -0,0 +1,370 @@
+//! 包解析，支持 package.json `exports`、`imports` 和 tsconfig `paths`
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+/// `@scope/pkg/sub` -> (`@scope/pkg`, `./sub`)，相对路径和绝对路径返回 `None`
+pub fn split_package_specifier(specifier: &str) -> Option<(&str, String)> {
+    if specifier.is_empty() || specifier.starts_with('.') || specifier.starts_with('/') {
+        return None;
+    }
+    let mut slashes = specifier.match_indices('/').map(|(index, _)| index);
+    let end = if specifier.starts_with('@') {
+        slashes.nth(1)
+    } else {
+        slashes.next()
+    };
+    match end {
+        Some(end) => Some((&specifier[..end], format!(".{}", &specifier[end..]))),
+        None if specifier.starts_with('@') && !specifier.contains('/') => None,
+        None => Some((specifier, ".".into())),
+    }
+}
+
+/// 从 `basedir` 向上查找 `node_modules/<name>`
+pub fn find_package_dir(name: &str, basedir: &Path) -> Option<PathBuf> {
+    basedir
+        .ancestors()
+        .map(|dir| dir.join("node_modules").join(name))
+        .find(|dir| dir.join("package.json").is_file())
+}
+
+/// package.json `exports`，条件需要保持顺序
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Exports {
+    Target(String),
+    Array(Vec<Exports>),
+    Map(IndexMap<String, Exports>),
+    Null,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct PackageJson {
+    exports: Option<Exports>,
+    imports: Option<Exports>,
+}
+
+fn read_package_json(dir: &Path) -> Option<PackageJson> {
+    serde_json::from_str(&fs::read_to_string(dir.join("package.json")).ok()?).ok()
+}
+
+impl Exports {
+    /// 条件对象按 key 顺序匹配，`default` 总是匹配
+    fn resolve_target(&self, star: Option<&str>, conditions: &[&str]) -> Option<String> {
+        match self {
+            Exports::Target(target) => Some(match star {
+                Some(star) => target.replace('*', star),
+                None => target.clone(),
+            }),
+            Exports::Array(targets) => targets
+                .iter()
+                .find_map(|target| target.resolve_target(star, conditions)),
+            Exports::Map(map) => map.iter().find_map(|(key, target)| {
+                (key == "default" || conditions.contains(&key.as_str()))
+                    .then(|| target.resolve_target(star, conditions))
+                    .flatten()
+            }),
+            Exports::Null => None,
+        }
+    }
+
+    /// 解析 `subpath`（`.`、`./sub` 或者 `imports` 中的 `#sub`），返回相对包目录的路径
+    pub fn resolve(&self, subpath: &str, conditions: &[&str]) -> Option<String> {
+        let map = match self {
+            Exports::Map(map)
+                if map
+                    .keys()
+                    .any(|key| key.starts_with('.') || key.starts_with('#')) =>
+            {
+                map
+            }
+            _ => {
+                return (subpath == ".")
+                    .then(|| self.resolve_target(None, conditions))
+                    .flatten()
+            }
+        };
+        if let Some(target) = map.get(subpath) {
+            return target.resolve_target(None, conditions);
+        }
+        // `./*`、`./icons/*.js`，前缀最长的优先
+        map.iter()
+            .filter_map(|(key, target)| {
+                let (prefix, suffix) = key.split_once('*')?;
+                let star = subpath.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                Some((prefix.len(), star, target))
+            })
+            .max_by_key(|(len, ..)| *len)
+            .and_then(|(_, star, target)| target.resolve_target(Some(star), conditions))
+    }
+}
+
+/// 解析有 `exports` 的包，没有 `exports` 时返回 `None`
+pub fn resolve_package_exports(
+    specifier: &str,
+    basedir: &Path,
+    conditions: &[&str],
+) -> Option<PathBuf> {
+    let (name, subpath) = split_package_specifier(specifier)?;
+    let dir = find_package_dir(name, basedir)?;
+    let target = read_package_json(&dir)?
+        .exports?
+        .resolve(&subpath, conditions)?;
+    Some(dir.join(target))
+}
+
+/// `#internal/x`，使用 `basedir` 所在包（最近的 package.json）的 `imports`，
+/// 目标可以是其他包
+pub fn resolve_package_imports(
+    specifier: &str,
+    basedir: &Path,
+    conditions: &[&str],
+) -> Option<PathBuf> {
+    let dir = basedir
+        .ancestors()
+        .find(|dir| dir.join("package.json").is_file())?;
+    let target = read_package_json(dir)?
+        .imports?
+        .resolve(specifier, conditions)?;
+    if target.starts_with("./") {
+        Some(dir.join(target))
+    } else {
+        resolve_package_exports(&target, dir, conditions)
+    }
+}
+
+/// 去掉注释和尾逗号，tsconfig 不是严格的 JSON
+fn strip_json_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => while chars.next_if(|c| *c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == '*' && chars.next_if_eq(&'/').is_some() {
+                        break;
+                    }
+                }
+            }
+            (']' | '}', _) => {
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct CompilerOptions {
+    base_url: Option<String>,
+    paths: IndexMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct TsConfigJson {
+    compiler_options: CompilerOptions,
+}
+
+/// tsconfig -> (mtime, paths)
+static TSCONFIG_CACHE: Lazy<
+    Mutex<HashMap<PathBuf, (Option<SystemTime>, Option<Arc<TsConfigPaths>>)>>,
+> = Lazy::new(Default::default);
+
+/// tsconfig `compilerOptions.paths` 和 `baseUrl`，不支持 `extends`
+#[derive(Debug, Default)]
+pub struct TsConfigPaths {
+    base_url: Option<PathBuf>,
+    /// `paths` 相对 `baseUrl`，没有时相对 tsconfig 所在目录
+    paths_base: PathBuf,
+    paths: IndexMap<String, Vec<String>>,
+}
+
+impl TsConfigPaths {
+    pub fn read(file: &Path) -> Option<Self> {
+        let content = strip_json_comments(&fs::read_to_string(file).ok()?);
+        let options = serde_json::from_str::<TsConfigJson>(&content)
+            .ok()?
+            .compiler_options;
+        let dir = file.parent().unwrap_or(Path::new(""));
+        let base_url = options.base_url.map(|base_url| dir.join(base_url));
+        Some(TsConfigPaths {
+            paths_base: base_url.clone().unwrap_or_else(|| dir.to_path_buf()),
+            base_url,
+            paths: options.paths,
+        })
+    }
+
+    /// 按文件 mtime 缓存，每个文件转换时都会读取
+    pub fn read_cached(file: &Path) -> Option<Arc<Self>> {
+        let mtime = fs::metadata(file).and_then(|x| x.modified()).ok();
+        let Ok(mut cache) = TSCONFIG_CACHE.lock() else {
+            return Self::read(file).map(Arc::new);
+        };
+        match cache.get(file) {
+            Some((cached, paths)) if *cached == mtime => paths.clone(),
+            _ => {
+                let paths = Self::read(file).map(Arc::new);
+                cache.insert(file.to_path_buf(), (mtime, paths.clone()));
+                paths
+            }
+        }
+    }
+
+    /// 按顺序尝试的路径：匹配的 `paths`（完全匹配优先，然后是前缀最长的），然后是 `baseUrl`
+    pub fn candidates(&self, specifier: &str) -> Vec<PathBuf> {
+        let targets = self
+            .paths
+            .get(specifier)
+            .map(|targets| (targets, ""))
+            .or_else(|| {
+                self.paths
+                    .iter()
+                    .filter_map(|(key, targets)| {
+                        let (prefix, suffix) = key.split_once('*')?;
+                        let star = specifier.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                        Some((prefix.len(), star, targets))
+                    })
+                    .max_by_key(|(len, ..)| *len)
+                    .map(|(_, star, targets)| (targets, star))
+            });
+        targets
+            .into_iter()
+            .flat_map(|(targets, star)| {
+                targets
+                    .iter()
+                    .map(move |target| self.paths_base.join(target.replace('*', star)))
+            })
+            .chain(
+                self.base_url
+                    .iter()
+                    .map(|base_url| base_url.join(specifier)),
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_split_package_specifier() {
+        assert_eq!(split_package_specifier("pkg"), Some(("pkg", ".".into())));
+        assert_eq!(
+            split_package_specifier("@scope/pkg/a/b"),
+            Some(("@scope/pkg", "./a/b".into()))
+        );
+        assert_eq!(split_package_specifier("./a"), None);
+    }
+
+    #[test]
+    fn should_resolve_exports() {
+        let exports: Exports = serde_json::from_str(
+            r#"{
+                ".": { "types": "./index.d.ts", "require": "./index.cjs", "import": "./index.js" },
+                "./auto-import": "./auto-import.json",
+                "./icons/*": { "require": "./cjs/*.js", "default": "./icons/*.js" },
+                "./internal/*": null
+            }"#,
+        )
+        .unwrap();
+        let conditions = ["import", "require"];
+        assert_eq!(
+            exports.resolve(".", &conditions),
+            Some("./index.cjs".into())
+        );
+        assert_eq!(
+            exports.resolve("./auto-import", &conditions),
+            Some("./auto-import.json".into())
+        );
+        assert_eq!(
+            exports.resolve("./icons/a", &["import"]),
+            Some("./icons/a.js".into())
+        );
+        assert_eq!(exports.resolve("./internal/a", &conditions), None);
+        assert_eq!(exports.resolve("./b", &conditions), None);
+        assert_eq!(
+            Exports::Target("./main.js".into()).resolve(".", &conditions),
+            Some("./main.js".into())
+        );
+        let imports: Exports = serde_json::from_str(
+            r##"{ "#internal/*": { "browser": "./src/browser/*.ts", "default": "./src/*.ts" } }"##,
+        )
+        .unwrap();
+        assert_eq!(
+            imports.resolve("#internal/a", &["browser"]),
+            Some("./src/browser/a.ts".into())
+        );
+    }
+
+    #[test]
+    fn should_resolve_tsconfig_paths() {
+        let dir = std::env::temp_dir().join(format!("gem-tsconfig-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("tsconfig.json"),
+            r#"{
+                // comment
+                "compilerOptions": {
+                    "baseUrl": "./src", /* comment */
+                    "paths": {
+                        "@/*": ["./*", "./gen/*"],
+                        "@/elements/*": ["./elements/*/index"],
+                        "config": ["./config.ts"],
+                    },
+                },
+            }"#,
+        )
+        .unwrap();
+        let tsconfig = TsConfigPaths::read(&dir.join("tsconfig.json"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let src = dir.join("src");
+        let tsconfig = tsconfig.unwrap();
+        assert_eq!(
+            tsconfig.candidates("@/a"),
+            vec![src.join("./a"), src.join("./gen/a"), src.join("@/a")]
+        );
+        assert_eq!(
+            tsconfig.candidates("@/elements/b"),
+            vec![src.join("./elements/b/index"), src.join("@/elements/b")]
+        );
+        assert_eq!(
+            tsconfig.candidates("config"),
+            vec![src.join("./config.ts"), src.join("config")]
+        );
+    }
+}
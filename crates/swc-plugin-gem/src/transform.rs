@@ -0,0 +1,181 @@
+use std::sync::Mutex;
+
+use swc_common::{
+    comments::SingleThreadedComments,
+    errors::{DiagnosticBuilder, Emitter, Handler, Level, HANDLER},
+    source_map::SourceMapGenConfig,
+    sync::Lrc,
+    FileName, Globals, Mark, SourceMap, GLOBALS,
+};
+use swc_core::ecma::{
+    codegen::{text_writer::JsWriter, Emitter as CodeEmitter},
+    parser::parse_file_as_module,
+    transforms::base::{fixer::fixer, resolver},
+    visit::VisitMutWith,
+};
+use swc_ecma_ast::{EsVersion, Program};
+
+use crate::{collect_report, gem_transform, registry::get_syntax, PluginConfig, TransformReport};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TransformOutput {
+    pub code: String,
+    /// Source map v3 JSON, include `sourcesContent`
+    pub map: String,
+    pub warnings: Vec<String>,
+    /// Not written, see `ReportConfig::path` and `write_report`
+    pub report: TransformReport,
+}
+
+/// Parse as module -> resolver -> gem passes (same order as the swc plugin) -> fixer -> codegen,
+/// the syntax is inferred from the `filename` extension.
+///
+/// Err: parse errors and transform errors
+pub fn transform(
+    source: String,
+    filename: &str,
+    config: &PluginConfig,
+) -> Result<TransformOutput, Vec<String>> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let diagnostics = Lrc::new(Mutex::new(Diagnostics::default()));
+    let handler = Handler::with_emitter(
+        true,
+        false,
+        Box::new(DiagnosticCollector {
+            cm: cm.clone(),
+            diagnostics: diagnostics.clone(),
+        }),
+    );
+
+    let (output, report) = collect_report(filename, || {
+        GLOBALS.set(&Globals::new(), || {
+            HANDLER.set(&handler, || run(&cm, &handler, source, filename, config))
+        })
+    });
+
+    let Diagnostics { warnings, errors } = diagnostics
+        .lock()
+        .map(|mut diagnostics| std::mem::take(&mut *diagnostics))
+        .unwrap_or_default();
+    match output {
+        Some((code, map)) if errors.is_empty() => Ok(TransformOutput {
+            code,
+            map,
+            warnings,
+            report,
+        }),
+        _ => Err(errors),
+    }
+}
+
+fn run(
+    cm: &Lrc<SourceMap>,
+    handler: &Handler,
+    source: String,
+    filename: &str,
+    config: &PluginConfig,
+) -> Option<(String, String)> {
+    let fm = cm.new_source_file(FileName::Real(filename.into()).into(), source);
+    let comments = SingleThreadedComments::default();
+    let mut errors = vec![];
+    let module = parse_file_as_module(
+        &fm,
+        get_syntax(filename),
+        EsVersion::latest(),
+        Some(&comments),
+        &mut errors,
+    );
+    errors
+        .into_iter()
+        .for_each(|err| err.into_diagnostic(handler).emit());
+    let mut program = match module {
+        Ok(module) => Program::Module(module),
+        Err(err) => {
+            err.into_diagnostic(handler).emit();
+            return None;
+        }
+    };
+
+    let unresolved_mark = Mark::new();
+    let top_level_mark = Mark::new();
+    program.mutate(resolver(unresolved_mark, top_level_mark, true));
+    program.visit_mut_with(&mut gem_transform(
+        config,
+        Some(filename.to_string()),
+        unresolved_mark,
+    ));
+    program.visit_mut_with(&mut fixer(Some(&comments)));
+    if handler.has_errors() {
+        return None;
+    }
+
+    let mut buf = vec![];
+    let mut mappings = vec![];
+    CodeEmitter {
+        cfg: Default::default(),
+        cm: cm.clone(),
+        comments: Some(&comments),
+        wr: JsWriter::new(cm.clone(), "\n", &mut buf, Some(&mut mappings)),
+    }
+    .emit_program(&program)
+    .ok()?;
+
+    let mut map = vec![];
+    cm.build_source_map(&mappings, None, SourceMapConfig)
+        .to_writer(&mut map)
+        .ok()?;
+
+    Some((String::from_utf8(buf).ok()?, String::from_utf8(map).ok()?))
+}
+
+struct SourceMapConfig;
+
+impl SourceMapGenConfig for SourceMapConfig {
+    fn file_name_to_source(&self, f: &FileName) -> String {
+        f.to_string()
+    }
+
+    fn inline_sources_content(&self, _: &FileName) -> bool {
+        true
+    }
+}
+
+#[derive(Default)]
+struct Diagnostics {
+    warnings: Vec<String>,
+    errors: Vec<String>,
+}
+
+/// 收集诊断信息，格式：`file:line:col: message`
+struct DiagnosticCollector {
+    cm: Lrc<SourceMap>,
+    diagnostics: Lrc<Mutex<Diagnostics>>,
+}
+
+impl Emitter for DiagnosticCollector {
+    fn emit(&mut self, db: &mut DiagnosticBuilder<'_>) {
+        let message = match db.span.primary_span() {
+            Some(span) if !span.is_dummy() => {
+                let loc = self.cm.lookup_char_pos(span.lo);
+                format!(
+                    "{}:{}:{}: {}",
+                    loc.file.name,
+                    loc.line,
+                    loc.col_display + 1,
+                    db.message()
+                )
+            }
+            _ => db.message(),
+        };
+        let Ok(mut diagnostics) = self.diagnostics.lock() else {
+            return;
+        };
+        match db.level {
+            Level::Bug | Level::Fatal | Level::PhaseFatal | Level::Error => {
+                diagnostics.errors.push(message)
+            }
+            Level::Warning => diagnostics.warnings.push(message),
+            _ => (),
+        }
+    }
+}
@@ -24,6 +24,7 @@
 //! 不再一样
 
 use std::{
+    collections::HashMap,
     hash::{DefaultHasher, Hash, Hasher},
     mem, vec,
 };
@@ -32,7 +33,7 @@ use indexmap::IndexSet;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::Deserialize;
-use swc_common::DUMMY_SP;
+use swc_common::{errors::HANDLER, Span, Spanned};
 use swc_core::{
     atoms::Atom,
     ecma::visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
@@ -40,11 +41,15 @@ use swc_core::{
 };
 use swc_ecma_ast::{
     op, ArrayLit, ArrowExpr, BinExpr, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, Class,
-    ClassMember, ClassMethod, ClassProp, Constructor, Decorator, Expr, ExprOrSpread, Function,
-    Ident, IdentName, ImportSpecifier, Lit, MemberExpr, MemberProp, MetaPropKind, MethodKind,
-    ModuleItem, Param, ParamOrTsParamProp, Pat, PropName, RestPat, ReturnStmt, StaticBlock, Stmt,
-    Str, ThisExpr,
+    ClassDecl, ClassExpr, ClassMember, ClassMethod, ClassProp, Constructor, Decorator, Expr,
+    ExprOrSpread, Function, Ident, IdentName, ImportSpecifier, Lit, MemberExpr, MemberProp,
+    MetaPropKind, MethodKind, ModuleItem, Param, ParamOrTsParamProp, Pat, PrivateName, PropName,
+    RestPat, ReturnStmt, StaticBlock, Stmt, Str, ThisExpr,
 };
+use tracing::debug;
+
+use super::hook::{Hook, HookContext, HookVisitor};
+use crate::report::{record, HmrClass, HmrStatus};
 
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
 #[serde(rename_all = "kebab-case")]
@@ -91,7 +96,8 @@ struct TransformVisitor {
     filename: String,
     class_index: usize,
     has_element: bool,
-    class_stack: Vec<String>,
+    // (tag name or hash key, class name)
+    class_stack: Vec<(String, Option<Atom>)>,
     need_reload: bool,
     imported_names: IndexSet<Atom>,
     target: HmrTarget,
@@ -99,11 +105,29 @@ struct TransformVisitor {
     // 语句，不支持嵌套类构造函数（嵌套类）；不能识别内部函数申明的
     // return 语句，有正常的内联函数 return 也会拒绝 hmr
     in_constructor: bool,
+    saved_in_constructor: Vec<bool>,
+    // 报告中使用的类名
+    class_ident: Option<Atom>,
+    // 改写后的私有成员名 -> 私有成员名，拒绝 hmr 时还原
+    private_names: HashMap<Atom, Atom>,
 }
 
 impl TransformVisitor {
-    fn get_current_tag_name(&self) -> &str {
-        self.class_stack.last().unwrap()
+    fn get_current_tag_name(&mut self, span: Span) -> Option<String> {
+        let tag_name = self
+            .class_stack
+            .last()
+            .map(|(tag_name, _)| tag_name.clone());
+        if tag_name.is_none() {
+            // 不能改写的私有成员访问，拒绝 hmr
+            self.need_reload = true;
+            HANDLER.with(|handler| {
+                handler
+                    .struct_span_warn(span, "hmr can't rewrite private name outside of class")
+                    .emit()
+            });
+        }
+        tag_name
     }
 
     fn store_arg_is_local(&self, expr: &Expr) -> bool {
@@ -167,23 +191,29 @@ fn get_shadow_ident(
         MethodKind::Setter => "_set",
         MethodKind::Method => "",
     };
-    format!(
-        "_hmr_{}_{}_{}{}",
-        if is_private { "private" } else { "public" },
-        &DASH_REG.replace_all(key, "_"),
-        origin_ident.as_ref(),
-        kind_suffix,
+    IdentName::new(
+        format!(
+            "_hmr_{}_{}_{}{}",
+            if is_private { "private" } else { "public" },
+            &DASH_REG.replace_all(key, "_"),
+            origin_ident.as_ref(),
+            kind_suffix,
+        )
+        .into(),
+        origin_ident.span,
     )
-    .into()
 }
 
 fn get_private_ident(origin_ident: &IdentName, key: &str) -> IdentName {
-    format!(
-        "_private_{}_{}",
-        &DASH_REG.replace_all(key, "_"),
-        origin_ident.as_ref()
+    IdentName::new(
+        format!(
+            "_private_{}_{}",
+            &DASH_REG.replace_all(key, "_"),
+            origin_ident.as_ref()
+        )
+        .into(),
+        origin_ident.span,
     )
-    .into()
 }
 
 fn gen_shadow_member(
@@ -192,11 +222,14 @@ fn gen_shadow_member(
     body: Option<BlockStmt>,
     params: Vec<Param>,
     is_async: bool,
+    span: Span,
 ) -> ClassMember {
     ClassMember::Method(ClassMethod {
+        span,
         is_static,
         key: PropName::Ident(shadow_ident.clone()),
         function: Box::new(Function {
+            span,
             is_async,
             params,
             body,
@@ -206,26 +239,27 @@ fn gen_shadow_member(
     })
 }
 
-fn gen_proxy_arg() -> Vec<Pat> {
+fn gen_proxy_arg(span: Span) -> Vec<Pat> {
     vec![Pat::Rest(RestPat {
         arg: Box::new(Pat::Ident("args".into())),
-        dot3_token: DUMMY_SP,
-        span: DUMMY_SP,
+        dot3_token: span,
+        span,
         type_ann: None,
     })]
 }
 
-fn gen_proxy_this_expr(shadow_ident: &IdentName) -> Expr {
+fn gen_proxy_this_expr(shadow_ident: &IdentName, span: Span) -> Expr {
     Expr::Member(MemberExpr {
-        obj: Box::new(Expr::This(ThisExpr { span: DUMMY_SP })),
+        span,
+        obj: Box::new(Expr::This(ThisExpr { span })),
         prop: MemberProp::Ident(shadow_ident.clone()),
-        ..Default::default()
     })
 }
 
-fn gen_proxy_body(shadow_ident: &IdentName) -> BlockStmt {
-    let this_expr = gen_proxy_this_expr(shadow_ident);
+fn gen_proxy_body(shadow_ident: &IdentName, span: Span) -> BlockStmt {
+    let this_expr = gen_proxy_this_expr(shadow_ident, span);
     BlockStmt {
+        span,
         stmts: vec![quote!(
             "return $expr.bind(this)(...args);" as Stmt,
             expr: Expr = this_expr
@@ -234,9 +268,10 @@ fn gen_proxy_body(shadow_ident: &IdentName) -> BlockStmt {
     }
 }
 
-fn gen_proxy_body_getter(shadow_ident: &IdentName) -> BlockStmt {
-    let this_expr = gen_proxy_this_expr(shadow_ident);
+fn gen_proxy_body_getter(shadow_ident: &IdentName, span: Span) -> BlockStmt {
+    let this_expr = gen_proxy_this_expr(shadow_ident, span);
     BlockStmt {
+        span,
         stmts: vec![quote!(
             "return $expr.bind(this)();" as Stmt,
             expr: Expr = this_expr
@@ -245,9 +280,10 @@ fn gen_proxy_body_getter(shadow_ident: &IdentName) -> BlockStmt {
     }
 }
 
-fn gen_proxy_body_setter(shadow_ident: &IdentName) -> BlockStmt {
-    let this_expr = gen_proxy_this_expr(shadow_ident);
+fn gen_proxy_body_setter(shadow_ident: &IdentName, span: Span) -> BlockStmt {
+    let this_expr = gen_proxy_this_expr(shadow_ident, span);
     BlockStmt {
+        span,
         stmts: vec![quote!(
             "$expr.bind(this)(value);" as Stmt,
             expr: Expr = this_expr
@@ -256,8 +292,8 @@ fn gen_proxy_body_setter(shadow_ident: &IdentName) -> BlockStmt {
     }
 }
 
-fn gen_proxy_body_constructor(shadow_ident: &IdentName, has_super: &bool) -> BlockStmt {
-    let this_expr = gen_proxy_this_expr(shadow_ident);
+fn gen_proxy_body_constructor(shadow_ident: &IdentName, has_super: &bool, span: Span) -> BlockStmt {
+    let this_expr = gen_proxy_this_expr(shadow_ident, span);
     let mut stmts = vec![];
     if *has_super {
         stmts.push(quote!("super();" as Stmt));
@@ -267,6 +303,7 @@ fn gen_proxy_body_constructor(shadow_ident: &IdentName, has_super: &bool) -> Blo
         expr: Expr = this_expr
     ));
     BlockStmt {
+        span,
         stmts,
         ..Default::default()
     }
@@ -278,16 +315,23 @@ fn replace_to_proxy_function(
     is_getter: bool,
     is_setter: bool,
 ) -> (Option<BlockStmt>, Vec<Param>) {
+    let span = func.body.as_ref().map_or(func.span, |body| body.span);
     if is_getter {
         return (
-            mem::replace(&mut func.body, gen_proxy_body_getter(shadow_ident).into()),
+            mem::replace(
+                &mut func.body,
+                gen_proxy_body_getter(shadow_ident, span).into(),
+            ),
             vec![],
         );
     }
     if is_setter {
         // setter 必须有且仅有一个参数，不能用 rest，否则下游解析会失败
         return (
-            mem::replace(&mut func.body, gen_proxy_body_setter(shadow_ident).into()),
+            mem::replace(
+                &mut func.body,
+                gen_proxy_body_setter(shadow_ident, span).into(),
+            ),
             mem::replace(
                 &mut func.params,
                 vec![Param::from(Pat::Ident("value".into()))],
@@ -295,10 +339,10 @@ fn replace_to_proxy_function(
         );
     }
     (
-        mem::replace(&mut func.body, gen_proxy_body(shadow_ident).into()),
+        mem::replace(&mut func.body, gen_proxy_body(shadow_ident, span).into()),
         mem::replace(
             &mut func.params,
-            gen_proxy_arg().drain(..).map(|x| x.into()).collect(),
+            gen_proxy_arg(span).drain(..).map(|x| x.into()).collect(),
         ),
     )
 }
@@ -308,13 +352,17 @@ fn replace_to_proxy_function_for_constructor(
     shadow_ident: &IdentName,
     has_super: &bool,
 ) -> (Option<BlockStmt>, Vec<Param>) {
+    let span = constructor
+        .body
+        .as_ref()
+        .map_or(constructor.span, |body| body.span);
     let body = mem::replace(
         &mut constructor.body,
-        gen_proxy_body_constructor(shadow_ident, has_super).into(),
+        gen_proxy_body_constructor(shadow_ident, has_super, span).into(),
     );
     let mut params = mem::replace(
         &mut constructor.params,
-        gen_proxy_arg()
+        gen_proxy_arg(span)
             .drain(..)
             .map(|x| ParamOrTsParamProp::Param(x.into()))
             .collect(),
@@ -354,14 +402,19 @@ fn replace_to_proxy_arrow(
     func: &mut ArrowExpr,
     shadow_ident: &IdentName,
 ) -> (BlockStmt, Vec<Param>) {
+    let span = func.body.span();
     let origin_body = mem::replace(
         &mut func.body,
-        Box::new(BlockStmtOrExpr::BlockStmt(gen_proxy_body(shadow_ident))),
+        Box::new(BlockStmtOrExpr::BlockStmt(gen_proxy_body(
+            shadow_ident,
+            span,
+        ))),
     );
     (
         match *origin_body {
             BlockStmtOrExpr::BlockStmt(body) => body,
             BlockStmtOrExpr::Expr(expr) => BlockStmt {
+                span,
                 stmts: vec![quote!(
                     "return $expr;" as Stmt,
                     expr: Expr = *expr
@@ -369,7 +422,7 @@ fn replace_to_proxy_arrow(
                 ..Default::default()
             },
         },
-        mem::replace(&mut func.params, gen_proxy_arg())
+        mem::replace(&mut func.params, gen_proxy_arg(span))
             .drain(..)
             .map(|x| x.into())
             .collect(),
@@ -383,8 +436,9 @@ fn transform_fn(
 ) -> (ClassMember, Option<ClassMember>) {
     match node {
         ClassMember::Constructor(mut constructor) => {
+            let span = constructor.span;
             let shadow_ident = get_shadow_ident(
-                &IdentName::from("constructor"),
+                &IdentName::new("constructor".into(), span),
                 key,
                 false,
                 MethodKind::Method,
@@ -396,7 +450,14 @@ fn transform_fn(
             );
             (
                 ClassMember::Constructor(Constructor { ..constructor }),
-                Some(gen_shadow_member(&shadow_ident, false, body, params, false)),
+                Some(gen_shadow_member(
+                    &shadow_ident,
+                    false,
+                    body,
+                    params,
+                    false,
+                    span,
+                )),
             )
         }
         ClassMember::Method(mut method) => {
@@ -409,6 +470,7 @@ fn transform_fn(
                     method.kind == MethodKind::Setter,
                 );
                 let is_async = method.function.is_async;
+                let span = method.span;
                 (
                     ClassMember::Method(ClassMethod { ..method }),
                     Some(gen_shadow_member(
@@ -417,6 +479,7 @@ fn transform_fn(
                         body,
                         params,
                         is_async,
+                        span,
                     )),
                 )
             } else {
@@ -424,7 +487,7 @@ fn transform_fn(
             }
         }
         ClassMember::PrivateMethod(mut method) => {
-            let origin_ident = IdentName::new(method.key.name, DUMMY_SP);
+            let origin_ident = IdentName::new(method.key.name, method.key.span);
             let private_ident = PropName::Ident(get_private_ident(&origin_ident, key));
             let shadow_ident = get_shadow_ident(&origin_ident, key, true, method.kind);
             let (body, params) = replace_to_proxy_function(
@@ -452,17 +515,19 @@ fn transform_fn(
                     body,
                     params,
                     is_async,
+                    method.span,
                 )),
             )
         }
         ClassMember::ClassProp(mut prop) => {
-            if let Some(ref mut v) = prop.value {
+            // 不支持计算属性名，当作字段处理
+            if let (Some(v), Some(origin_ident)) = (&mut prop.value, prop.key.as_ident()) {
                 if let Some(func) = v.as_mut_arrow() {
-                    let origin_ident = prop.key.as_ident().unwrap();
                     let shadow_ident =
                         get_shadow_ident(origin_ident, key, false, MethodKind::Method);
                     let (body, params) = replace_to_proxy_arrow(func, &shadow_ident);
                     let is_async = func.is_async;
+                    let span = prop.span;
                     return (
                         ClassMember::ClassProp(ClassProp { ..prop }),
                         Some(gen_shadow_member(
@@ -471,6 +536,7 @@ fn transform_fn(
                             Some(body),
                             params,
                             is_async,
+                            span,
                         )),
                     );
                 }
@@ -478,7 +544,7 @@ fn transform_fn(
             (ClassMember::ClassProp(prop), None)
         }
         ClassMember::PrivateProp(mut prop) => {
-            let origin_ident = IdentName::new(prop.key.name, DUMMY_SP);
+            let origin_ident = IdentName::new(prop.key.name, prop.key.span);
             let private_ident = PropName::Ident(get_private_ident(&origin_ident, key));
             if let Some(ref mut v) = prop.value {
                 if let Some(func) = v.as_mut_arrow() {
@@ -507,6 +573,7 @@ fn transform_fn(
                             Some(body),
                             params,
                             is_async,
+                            prop.span,
                         )),
                     );
                 }
@@ -586,7 +653,7 @@ fn get_field(origin_member: &ClassMember) -> Option<FieldProp> {
     }
 }
 
-fn gen_hmr_props(props: Vec<Option<FieldProp>>) -> ClassMember {
+fn gen_hmr_props(props: Vec<Option<FieldProp>>, span: Span) -> ClassMember {
     let elements = props
         .iter()
         .filter(|x| x.is_some())
@@ -618,87 +685,146 @@ fn gen_hmr_props(props: Vec<Option<FieldProp>>) -> ClassMember {
         ..Default::default()
     });
     ClassMember::StaticBlock(StaticBlock {
+        span,
         body: BlockStmt {
+            span,
             stmts: vec![quote!(
                 "this._defined_fields_ = $arr_expr;" as Stmt,
                 arr_expr: Expr = arr_expr
             )],
             ..Default::default()
         },
-        ..Default::default()
     })
 }
 
-fn gen_register_class(name: &str) -> Decorator {
+fn gen_register_class(name: &str, span: Span) -> Decorator {
     let name = Expr::Lit(Lit::Str(name.into()));
     Decorator {
+        span,
         expr: Box::new(quote!(
             "(window._hmrRegisterClass ? _hmrRegisterClass($key) : Function.prototype)" as Expr,
             key: Expr = name,
         )),
-        ..Default::default()
     }
 }
 
-impl VisitMut for TransformVisitor {
+/// 还原类中改写的私有成员名，不处理嵌套的类
+struct RestorePrivateVisitor<'a> {
+    private_names: &'a HashMap<Atom, Atom>,
+}
+
+impl VisitMut for RestorePrivateVisitor<'_> {
     noop_visit_mut_type!();
 
+    fn visit_mut_class(&mut self, _: &mut Class) {}
+
     fn visit_mut_member_expr(&mut self, node: &mut MemberExpr) {
         node.visit_mut_children_with(self);
 
-        if let Some(private_name) = node.prop.as_private_name() {
-            node.prop = MemberProp::Ident(get_private_ident(
-                &IdentName::new(private_name.name.clone(), DUMMY_SP),
-                self.get_current_tag_name(),
-            ));
+        if let MemberProp::Ident(ident) = &node.prop {
+            if let Some(name) = self.private_names.get(&ident.sym) {
+                node.prop = MemberProp::PrivateName(PrivateName {
+                    span: ident.span,
+                    name: name.clone(),
+                });
+            }
         }
     }
 
     fn visit_mut_bin_expr(&mut self, node: &mut BinExpr) {
         node.visit_mut_children_with(self);
 
+        if node.op == op!("in") {
+            if let Expr::Lit(Lit::Str(key)) = node.left.as_ref() {
+                let name = key
+                    .value
+                    .as_str()
+                    .and_then(|x| self.private_names.get(&Atom::from(x)));
+                if let Some(name) = name {
+                    *node.left = Expr::PrivateName(PrivateName {
+                        span: key.span,
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Hook for TransformVisitor {
+    fn exit_member_expr(&mut self, node: &mut MemberExpr, _: &mut HookContext) {
+        if let Some(private_name) = node.prop.as_private_name() {
+            let (name, span) = (private_name.name.clone(), private_name.span);
+            if let Some(tag_name) = self.get_current_tag_name(node.span) {
+                let private_ident =
+                    get_private_ident(&IdentName::new(name.clone(), span), &tag_name);
+                self.private_names.insert(private_ident.sym.clone(), name);
+                node.prop = MemberProp::Ident(private_ident);
+            }
+        }
+    }
+
+    fn exit_bin_expr(&mut self, node: &mut BinExpr, _: &mut HookContext) {
         if node.op == op!("in") {
             if let Expr::PrivateName(private_name) = node.left.as_ref() {
-                let private_ident = get_private_ident(
-                    &IdentName::new(private_name.name.clone(), DUMMY_SP),
-                    self.get_current_tag_name(),
-                );
-                *node.left = Expr::Lit(Lit::Str(Str::from(private_ident.sym.clone())));
+                let (name, span) = (private_name.name.clone(), private_name.span);
+                if let Some(tag_name) = self.get_current_tag_name(node.span) {
+                    let private_ident =
+                        get_private_ident(&IdentName::new(name.clone(), span), &tag_name);
+                    self.private_names.insert(private_ident.sym.clone(), name);
+                    *node.left = Expr::Lit(Lit::Str(Str {
+                        span,
+                        value: private_ident.sym.into(),
+                        raw: None,
+                    }));
+                }
             }
         }
     }
 
-    fn visit_mut_constructor(&mut self, node: &mut Constructor) {
+    fn enter_constructor(&mut self, _: &mut Constructor, _: &mut HookContext) -> bool {
         self.in_constructor = true;
-        node.visit_mut_children_with(self);
+        true
+    }
+
+    fn exit_constructor(&mut self, _: &mut Constructor, _: &mut HookContext) {
         self.in_constructor = false;
     }
 
-    fn visit_mut_function(&mut self, node: &mut Function) {
-        let saved = self.in_constructor;
+    fn enter_function(&mut self, _: &mut Function, _: &mut HookContext) -> bool {
+        self.saved_in_constructor.push(self.in_constructor);
         self.in_constructor = false;
-        node.visit_mut_children_with(self);
-        self.in_constructor = saved;
+        true
+    }
+
+    fn exit_function(&mut self, _: &mut Function, _: &mut HookContext) {
+        self.in_constructor = self.saved_in_constructor.pop().unwrap_or_default();
     }
 
-    fn visit_mut_arrow_expr(&mut self, node: &mut ArrowExpr) {
-        let saved = self.in_constructor;
+    fn enter_arrow_expr(&mut self, _: &mut ArrowExpr, _: &mut HookContext) -> bool {
+        self.saved_in_constructor.push(self.in_constructor);
         self.in_constructor = false;
-        node.visit_mut_children_with(self);
-        self.in_constructor = saved;
+        true
+    }
+
+    fn exit_arrow_expr(&mut self, _: &mut ArrowExpr, _: &mut HookContext) {
+        self.in_constructor = self.saved_in_constructor.pop().unwrap_or_default();
     }
 
-    fn visit_mut_param_or_ts_param_prop(&mut self, node: &mut ParamOrTsParamProp) {
+    fn enter_param_or_ts_param_prop(
+        &mut self,
+        node: &mut ParamOrTsParamProp,
+        _: &mut HookContext,
+    ) -> bool {
         if let ParamOrTsParamProp::TsParamProp(_) = node {
             if self.in_constructor {
                 self.need_reload = true;
             }
         }
+        false
     }
 
-    fn visit_mut_call_expr(&mut self, node: &mut CallExpr) {
-        node.visit_mut_children_with(self);
-
+    fn exit_call_expr(&mut self, node: &mut CallExpr, _: &mut HookContext) {
         if let Callee::Super(_) = node.callee {
             if !node.args.is_empty() {
                 self.need_reload = true;
@@ -706,46 +832,68 @@ impl VisitMut for TransformVisitor {
         }
     }
 
-    fn visit_mut_import_specifier(&mut self, node: &mut ImportSpecifier) {
+    fn enter_import_specifier(&mut self, node: &mut ImportSpecifier, _: &mut HookContext) -> bool {
         self.imported_names.insert(node.local().sym.clone());
-        node.visit_mut_children_with(self);
+        true
     }
 
-    fn visit_mut_meta_prop_kind(&mut self, node: &mut MetaPropKind) {
+    fn enter_meta_prop_kind(&mut self, node: &mut MetaPropKind, _: &mut HookContext) -> bool {
         if node == &MetaPropKind::NewTarget {
             self.need_reload = true;
         }
+        true
     }
 
-    fn visit_mut_return_stmt(&mut self, node: &mut ReturnStmt) {
-        node.visit_mut_children_with(self);
-
+    fn exit_return_stmt(&mut self, node: &mut ReturnStmt, _: &mut HookContext) {
         if self.in_constructor && node.arg.is_some() {
             self.need_reload = true;
         }
     }
 
-    fn visit_mut_class(&mut self, node: &mut Class) {
-        let has_super = node.super_class.is_some();
+    fn enter_class_decl(&mut self, node: &mut ClassDecl, _: &mut HookContext) -> bool {
+        self.class_ident = Some(node.ident.sym.clone());
+        true
+    }
+
+    fn enter_class_expr(&mut self, node: &mut ClassExpr, _: &mut HookContext) -> bool {
+        self.class_ident = node.ident.as_ref().map(|ident| ident.sym.clone());
+        true
+    }
+
+    fn enter_class(&mut self, node: &mut Class, _: &mut HookContext) -> bool {
+        // 已经拒绝 hmr，类保持不变
+        if self.need_reload {
+            return false;
+        }
+
         let class_name = self.get_class_name(node);
+        let class_ident = self.class_ident.take();
+
+        if class_name.is_empty() {
+            self.need_reload = true;
+            return false;
+        }
+
+        self.class_stack.push((class_name, class_ident));
+        true
+    }
+
+    fn exit_class(&mut self, node: &mut Class, _: &mut HookContext) {
+        let Some((class_name, class_ident)) = self.class_stack.pop() else {
+            return;
+        };
+        let has_super = node.super_class.is_some();
 
-        if !class_name.is_empty() {
-            self.class_stack.push(class_name.clone());
-            let snapshot = node.body.clone();
-            node.visit_mut_children_with(self);
-            self.class_stack.pop();
-
-            if !self.need_reload {
-                for decorator in &node.decorators {
-                    if let Some(call) = decorator.expr.as_call() {
-                        if let Callee::Expr(callee) = &call.callee {
-                            if let Some(Ident { sym, .. }) = callee.as_ident() {
-                                if sym.as_str() == "connectStore" {
-                                    if let Some(ExprOrSpread { expr, .. }) = call.args.first() {
-                                        if self.store_arg_is_local(expr) {
-                                            self.need_reload = true;
-                                            break;
-                                        }
+        if !self.need_reload {
+            for decorator in &node.decorators {
+                if let Some(call) = decorator.expr.as_call() {
+                    if let Callee::Expr(callee) = &call.callee {
+                        if let Some(Ident { sym, .. }) = callee.as_ident() {
+                            if sym.as_str() == "connectStore" {
+                                if let Some(ExprOrSpread { expr, .. }) = call.args.first() {
+                                    if self.store_arg_is_local(expr) {
+                                        self.need_reload = true;
+                                        break;
                                     }
                                 }
                             }
@@ -753,50 +901,59 @@ impl VisitMut for TransformVisitor {
                     }
                 }
             }
+        }
 
-            if self.need_reload {
-                node.body = snapshot;
-                return;
-            }
+        if self.need_reload {
+            node.body.visit_mut_with(&mut RestorePrivateVisitor {
+                private_names: &self.private_names,
+            });
+            return;
+        }
 
-            if !class_name.starts_with(HASH_KEY_PREFIX) {
-                self.has_element = true;
-            }
+        if !class_name.starts_with(HASH_KEY_PREFIX) {
+            self.has_element = true;
+        }
 
-            let mut props = vec![];
-            let mut body = vec![];
-            while let Some(item) = node.body.pop() {
-                let (origin_member, append) = transform_fn(item, &class_name, &has_super);
+        let mut props = vec![];
+        let mut body = vec![];
+        while let Some(item) = node.body.pop() {
+            let (origin_member, append) = transform_fn(item, &class_name, &has_super);
 
-                // 不是函数成员，进行记录
-                if append.is_none() {
-                    props.push(get_field(&origin_member));
-                }
+            // 不是函数成员，进行记录
+            if append.is_none() {
+                props.push(get_field(&origin_member));
+            }
 
-                body.push(origin_member);
+            body.push(origin_member);
 
-                if let Some(shadow_member) = append {
-                    body.push(shadow_member);
-                }
+            if let Some(shadow_member) = append {
+                body.push(shadow_member);
             }
-            body.reverse();
-            node.body = body;
-
-            node.body.push(gen_hmr_props(props));
-            node.decorators.push(gen_register_class(&class_name));
-        } else {
-            self.need_reload = true;
         }
+        body.reverse();
+        node.body = body;
+
+        node.body.push(gen_hmr_props(props, node.span));
+        node.decorators
+            .push(gen_register_class(&class_name, node.span));
+
+        debug!("register hmr class `{class_name}`");
+        record(|report| {
+            report.hmr_classes.push(HmrClass {
+                name: class_ident.map(|x| x.to_string()),
+                key: class_name,
+            })
+        });
     }
 
-    fn visit_mut_module_items(&mut self, node: &mut Vec<ModuleItem>) {
-        node.visit_mut_children_with(self);
-
+    fn exit_module_items(&mut self, node: &mut Vec<ModuleItem>, _: &mut HookContext) {
         let Some(hot_expr) = gen_hot_expr(self.target) else {
             return;
         };
 
         if self.need_reload {
+            debug!("decline hmr");
+            record(|report| report.hmr_status = Some(HmrStatus::Decline));
             node.push(quote!(
                 "
                 if ($hot) {
@@ -806,6 +963,8 @@ impl VisitMut for TransformVisitor {
                 hot: Expr = hot_expr
             ));
         } else if self.has_element {
+            debug!("accept hmr");
+            record(|report| report.hmr_status = Some(HmrStatus::Accept));
             node.push(quote!(
                 "
                 if ($hot) {
@@ -827,10 +986,14 @@ fn gen_hot_expr(target: HmrTarget) -> Option<Expr> {
     }
 }
 
-pub fn hmr_transform(filename: Option<String>, target: HmrTarget) -> impl VisitMut {
+pub fn hmr_hook(filename: Option<String>, target: HmrTarget) -> impl Hook {
     TransformVisitor {
         filename: filename.unwrap_or_default(),
         target,
         ..Default::default()
     }
 }
+
+pub fn hmr_transform(filename: Option<String>, target: HmrTarget) -> impl VisitMut {
+    HookVisitor::new(vec![Box::new(hmr_hook(filename, target))])
+}
@@ -0,0 +1,164 @@
+//! 所有转换共享一次遍历
+//!
+//! 每个转换实现 `Hook`，在节点的子节点访问前后调用 `enter_*` / `exit_*`，
+//! 多个 `Hook` 按添加顺序（也就是原来的 pass 顺序）执行：
+//!
+//! - `enter_*` 返回 `false` 时该 `Hook` 跳过这个节点的子节点，也不会调用 `exit_*`
+//! - 生成的新节点使用 `HookContext::visit` 交给后面的 `Hook` 处理，
+//!   和多次遍历时后面的 pass 能看到前面 pass 生成的节点一样
+
+use swc_core::ecma::visit::{noop_visit_mut_type, VisitMut, VisitMutWith};
+use swc_ecma_ast::{
+    ArrowExpr, BinExpr, CallExpr, Callee, Class, ClassDecl, ClassExpr, ClassMember, Constructor,
+    ExportAll, FnDecl, FnExpr, Function, Ident, ImportDecl, ImportSpecifier, KeyValueProp,
+    MemberExpr, MetaPropKind, ModuleItem, NamedExport, NewExpr, ObjectLit, ParamOrTsParamProp,
+    PrivateMethod, ReturnStmt, TaggedTpl, VarDeclarator,
+};
+
+pub struct HookContext<'a> {
+    visitor: &'a mut HookVisitor,
+    next: usize,
+}
+
+impl HookContext<'_> {
+    /// Run the following hooks on a generated node
+    pub fn visit<N: VisitMutWith<HookVisitor>>(&mut self, node: &mut N) {
+        let first = self.visitor.first;
+        self.visitor.first = self.next;
+        node.visit_mut_with(self.visitor);
+        self.visitor.first = first;
+    }
+}
+
+type Enter<N> = fn(&mut dyn Hook, &mut N, &mut HookContext) -> bool;
+type Exit<N> = fn(&mut dyn Hook, &mut N, &mut HookContext);
+
+#[derive(Default)]
+pub struct HookVisitor {
+    hooks: Vec<Option<Box<dyn Hook>>>,
+    /// 跳过子节点时的深度
+    skipped: Vec<Option<usize>>,
+    depth: usize,
+    first: usize,
+}
+
+impl HookVisitor {
+    pub fn new(hooks: Vec<Box<dyn Hook>>) -> Self {
+        HookVisitor {
+            skipped: vec![None; hooks.len()],
+            hooks: hooks.into_iter().map(Some).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn dispatch<N>(&mut self, node: &mut N, enter: Enter<N>, exit: Exit<N>)
+    where
+        N: VisitMutWith<HookVisitor>,
+    {
+        self.depth += 1;
+        let depth = self.depth;
+
+        for index in self.first..self.hooks.len() {
+            if self.skipped[index].is_some() {
+                continue;
+            }
+            // 取出当前 hook，让它可以通过 `HookContext` 访问后面的 hook
+            let Some(mut hook) = self.hooks[index].take() else {
+                continue;
+            };
+            let mut ctx = HookContext {
+                visitor: self,
+                next: index + 1,
+            };
+            if !enter(hook.as_mut(), node, &mut ctx) {
+                self.skipped[index] = Some(depth);
+            }
+            self.hooks[index] = Some(hook);
+        }
+
+        node.visit_mut_children_with(self);
+
+        for index in self.first..self.hooks.len() {
+            match self.skipped[index] {
+                Some(skipped) if skipped == depth => {
+                    self.skipped[index] = None;
+                    continue;
+                }
+                Some(_) => continue,
+                None => {}
+            }
+            let Some(mut hook) = self.hooks[index].take() else {
+                continue;
+            };
+            let mut ctx = HookContext {
+                visitor: self,
+                next: index + 1,
+            };
+            exit(hook.as_mut(), node, &mut ctx);
+            self.hooks[index] = Some(hook);
+        }
+
+        self.depth -= 1;
+    }
+}
+
+macro_rules! define_hooks {
+    ($($visit:ident, $enter:ident, $exit:ident: $ty:ty;)*) => {
+        #[allow(unused_variables)]
+        pub trait Hook {
+            $(
+                fn $enter(&mut self, node: &mut $ty, ctx: &mut HookContext) -> bool {
+                    true
+                }
+
+                fn $exit(&mut self, node: &mut $ty, ctx: &mut HookContext) {}
+            )*
+        }
+
+        impl VisitMut for HookVisitor {
+            noop_visit_mut_type!();
+
+            $(
+                fn $visit(&mut self, node: &mut $ty) {
+                    self.dispatch(
+                        node,
+                        |hook, node, ctx| hook.$enter(node, ctx),
+                        |hook, node, ctx| hook.$exit(node, ctx),
+                    );
+                }
+            )*
+        }
+    };
+}
+
+define_hooks! {
+    visit_mut_module_items, enter_module_items, exit_module_items: Vec<ModuleItem>;
+    visit_mut_import_decl, enter_import_decl, exit_import_decl: ImportDecl;
+    visit_mut_import_specifier, enter_import_specifier, exit_import_specifier: ImportSpecifier;
+    visit_mut_named_export, enter_named_export, exit_named_export: NamedExport;
+    visit_mut_export_all, enter_export_all, exit_export_all: ExportAll;
+    visit_mut_ident, enter_ident, exit_ident: Ident;
+    visit_mut_object_lit, enter_object_lit, exit_object_lit: ObjectLit;
+    visit_mut_key_value_prop, enter_key_value_prop, exit_key_value_prop: KeyValueProp;
+    visit_mut_tagged_tpl, enter_tagged_tpl, exit_tagged_tpl: TaggedTpl;
+    visit_mut_class_decl, enter_class_decl, exit_class_decl: ClassDecl;
+    visit_mut_class_expr, enter_class_expr, exit_class_expr: ClassExpr;
+    visit_mut_class, enter_class, exit_class: Class;
+    visit_mut_class_members, enter_class_members, exit_class_members: Vec<ClassMember>;
+    visit_mut_class_member, enter_class_member, exit_class_member: ClassMember;
+    visit_mut_private_method, enter_private_method, exit_private_method: PrivateMethod;
+    visit_mut_constructor, enter_constructor, exit_constructor: Constructor;
+    visit_mut_param_or_ts_param_prop, enter_param_or_ts_param_prop, exit_param_or_ts_param_prop: ParamOrTsParamProp;
+    visit_mut_fn_decl, enter_fn_decl, exit_fn_decl: FnDecl;
+    visit_mut_fn_expr, enter_fn_expr, exit_fn_expr: FnExpr;
+    visit_mut_function, enter_function, exit_function: Function;
+    visit_mut_arrow_expr, enter_arrow_expr, exit_arrow_expr: ArrowExpr;
+    visit_mut_var_declarator, enter_var_declarator, exit_var_declarator: VarDeclarator;
+    visit_mut_call_expr, enter_call_expr, exit_call_expr: CallExpr;
+    visit_mut_new_expr, enter_new_expr, exit_new_expr: NewExpr;
+    visit_mut_callee, enter_callee, exit_callee: Callee;
+    visit_mut_member_expr, enter_member_expr, exit_member_expr: MemberExpr;
+    visit_mut_bin_expr, enter_bin_expr, exit_bin_expr: BinExpr;
+    visit_mut_meta_prop_kind, enter_meta_prop_kind, exit_meta_prop_kind: MetaPropKind;
+    visit_mut_return_stmt, enter_return_stmt, exit_return_stmt: ReturnStmt;
+}
@@ -1,30 +1,63 @@
-use std::{collections::HashMap, env, fs, path::Path};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    fs, mem,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
 
 use indexmap::{IndexMap, IndexSet};
 use node_resolve::Resolver;
 use once_cell::sync::Lazy;
+use pathdiff::diff_paths;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use swc_common::{Spanned, SyntaxContext, DUMMY_SP};
+use swc_common::{errors::HANDLER, BytePos, Mark, Span, Spanned, SyntaxContext, DUMMY_SP};
 use swc_core::{
     atoms::Atom,
-    ecma::visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
+    ecma::visit::{Visit, VisitMut, VisitWith},
+    quote,
 };
 use swc_ecma_ast::{
-    ClassDecl, ClassExpr, FnDecl, FnExpr, Id, Ident, ImportDecl, ImportNamedSpecifier,
-    ImportSpecifier, ModuleDecl, ModuleExportName, ModuleItem, Str, TaggedTpl, VarDeclarator,
+    ArrayLit, ArrowExpr, BlockStmt, BlockStmtOrExpr, CallExpr, Class, Expr, ExprOrSpread, FnExpr,
+    Function, Ident, ImportDecl, ImportNamedSpecifier, ImportPhase, ImportSpecifier, KeyValueProp,
+    Lit, ModuleDecl, ModuleExportName, ModuleItem, ObjectLit, Prop, PropName, PropOrSpread, Stmt,
+    Str, TaggedTpl, TsEntityName, TsExprWithTypeArgs, TsTypeRef,
+};
+use tracing::debug;
+
+use super::{
+    hook::{Hook, HookContext, HookVisitor},
+    path::{converting_to_unix_path, extension_map, get_cwd, replace_extension},
+};
+use crate::{
+    html::{is_custom_element_name, scan_custom_elements},
+    registry::{custom_element_tag, load_registry},
+    report::{record, ImportedElement, ImportedMember},
+    resolve::{resolve_package_exports, split_package_specifier},
+    suggest::did_you_mean,
 };
 
-static CUSTOM_ELEMENT_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(?s)<(?<tag>\w+(-\w+)+)(\s|>)").unwrap());
+static CASE_TEMPLATE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{:(?<case>pascal|kebab|camel):\}").unwrap());
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum MemberOrMemberAs {
     Member(String),
     MemberAs([String; 2]),
 }
 
+impl MemberOrMemberAs {
+    fn local(&self) -> &str {
+        match self {
+            MemberOrMemberAs::Member(name) => name,
+            MemberOrMemberAs::MemberAs([_, member_as]) => member_as,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct RegexStringPair {
     #[serde(with = "serde_regex")]
@@ -32,22 +65,441 @@ struct RegexStringPair {
     path: String,
 }
 
+/// `members` 中使用 `*` 的成员，例如 `"icons/{:kebab:}": [["default", "Icon*"]]`
+#[derive(Debug, Serialize, Deserialize)]
+struct MemberPattern {
+    #[serde(with = "serde_regex")]
+    regex: Regex,
+    imported: Option<Atom>,
+    /// 可能包含 `{:pascal:}`、`{:kebab:}`、`{:camel:}`
+    package: String,
+    /// 匹配但不导入的成员
+    exclude: Vec<String>,
+}
+
+impl MemberPattern {
+    /// `*` 匹配的部分
+    fn captures<'a>(&self, local: &'a str) -> Option<&'a str> {
+        if self.exclude.iter().any(|x| x == local) {
+            return None;
+        }
+        let cap = self.regex.captures(local)?;
+        Some(cap.get(1).map_or(local, |x| x.as_str()))
+    }
+}
+
+/// (package, local) -> 所在配置和继承它的配置的 `exclude`
+type ScopedExclude = HashMap<(String, String), Vec<String>>;
+
 #[derive(Deserialize, Serialize, Default)]
-struct AutoImportConfig {
+struct MemberMap {
     /// local -> (imported, package name)
-    member_map: HashMap<String, (Option<Atom>, String)>,
+    exact: HashMap<String, (Option<Atom>, String)>,
+    patterns: Vec<MemberPattern>,
+}
+
+impl MemberMap {
+    fn get(&self, local: &str) -> Option<(Option<Atom>, String)> {
+        if let Some(exact) = self.exact.get(local) {
+            return Some(exact.clone());
+        }
+        self.patterns.iter().find_map(|pattern| {
+            let name = pattern.captures(local)?;
+            Some((
+                pattern.imported.clone(),
+                expand_package(&pattern.package, name),
+            ))
+        })
+    }
+
+    fn contains_key(&self, local: &str) -> bool {
+        self.exact.contains_key(local) || self.patterns.iter().any(|x| x.captures(local).is_some())
+    }
+}
+
+fn split_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words: Vec<String> = vec![];
+    let mut word = String::new();
+    for (index, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            words.extend((!word.is_empty()).then(|| mem::take(&mut word)));
+            continue;
+        }
+        let prev = index.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(index + 1);
+        // `colorPicker`、`HTMLElement`
+        let boundary = c.is_uppercase()
+            && prev.is_some_and(|prev| {
+                prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            });
+        if boundary {
+            words.extend((!word.is_empty()).then(|| mem::take(&mut word)));
+        }
+        word.push(c);
+    }
+    words.extend((!word.is_empty()).then_some(word));
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or(String::new(), |first| {
+        first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect()
+    })
+}
+
+/// 第一个参数是自定义元素名称的字符串
+fn tag_arg(call: &CallExpr) -> Option<(String, Span)> {
+    match call.args.first()?.expr.as_lit()? {
+        Lit::Str(tag) => {
+            let value = tag.value.as_str()?;
+            is_custom_element_name(value).then(|| (value.to_string(), tag.span))
+        }
+        _ => None,
+    }
+}
+
+/// `gem-(link|route)` -> `gem-link`, `gem-route`，包含 `*` 的模式没有确定的模块
+fn expand_tag_pattern(pattern: &str) -> Vec<String> {
+    static PATTERN_REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?<prefix>[\w-]*)(\((?<alternatives>[\w|-]+)\))?(?<suffix>[\w-]*)$").unwrap()
+    });
+    let Some(cap) = PATTERN_REGEX.captures(pattern) else {
+        return vec![];
+    };
+    let alternatives = cap.name("alternatives").map_or("", |x| x.as_str());
+    alternatives
+        .split('|')
+        .map(|x| format!("{}{x}{}", &cap["prefix"], &cap["suffix"]))
+        .filter(|tag| tag.contains('-'))
+        .collect()
+}
+
+/// `@mantou/gem/{:kebab:}` + `ColorPicker` -> `@mantou/gem/color-picker`
+fn expand_package(package: &str, name: &str) -> String {
+    let words = split_words(name);
+    CASE_TEMPLATE_REGEX
+        .replace_all(package, |cap: &regex::Captures| match &cap["case"] {
+            "kebab" => words
+                .iter()
+                .map(|x| x.to_lowercase())
+                .collect::<Vec<_>>()
+                .join("-"),
+            "camel" => words
+                .iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    if i == 0 {
+                        x.to_lowercase()
+                    } else {
+                        capitalize(x)
+                    }
+                })
+                .collect(),
+            _ => words.iter().map(|x| capitalize(x)).collect(),
+        })
+        .into_owned()
+}
+
+/// `Store<T = any>` -> (`Store`, `T = any`)
+fn split_type_params(name: &str) -> (&str, Option<&str>) {
+    match name.split_once('<') {
+        Some((name, params)) => {
+            let params = params.trim();
+            (
+                name.trim(),
+                Some(params.strip_suffix('>').unwrap_or(params).trim()),
+            )
+        }
+        None => (name, None),
+    }
+}
+
+/// `K extends string, V = Record<K, any>` -> `K, V`
+fn type_args(params: &str) -> String {
+    let mut args = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in params.char_indices() {
+        match c {
+            '<' | '(' | '[' | '{' => depth += 1,
+            '>' | ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(&params[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(&params[start..]);
+    args.iter()
+        .filter_map(|param| param.split_whitespace().next())
+        .map(|param| param.trim_end_matches('='))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 去掉类型名称中的类型参数，记录到 `type_params`，`["Store", "AppStore<T = any>"]` 的参数写在任意一边
+fn take_type_params(
+    member: MemberOrMemberAs,
+    type_params: &mut HashMap<String, String>,
+) -> MemberOrMemberAs {
+    let (imported, local) = match &member {
+        MemberOrMemberAs::Member(name) => (None, name),
+        MemberOrMemberAs::MemberAs([name, member_as]) => (Some(split_type_params(name)), member_as),
+    };
+    let (local, params) = split_type_params(local);
+    if let Some(params) = params.or(imported.and_then(|(_, params)| params)) {
+        type_params.insert(local.into(), params.into());
+    }
+    match imported {
+        Some((imported, _)) => MemberOrMemberAs::MemberAs([imported.into(), local.into()]),
+        None => MemberOrMemberAs::Member(local.into()),
+    }
+}
+
+#[derive(Deserialize, Serialize, Default)]
+struct AutoImportConfig {
+    member_map: MemberMap,
+    /// 类型，使用 `import type` 导入
+    type_map: MemberMap,
+    /// 类型 local -> 类型参数，例如 `T = any`
+    type_params: HashMap<String, String>,
     tag_config: Vec<RegexStringPair>,
+    report_unknown_elements: bool,
+    /// 扫描的目录或者 JSON 注册表
+    registry: Option<PathBuf>,
 }
 
 #[derive(Default)]
 struct TransformVisitor {
-    config: AutoImportConfig,
-    used_members: IndexSet<Id>,
-    defined_members: IndexSet<Id>,
+    config: Arc<AutoImportConfig>,
+    lazy_view: bool,
+    filename: Option<String>,
+    /// 项目中定义的元素，tag -> 文件
+    registry: IndexMap<String, PathBuf>,
+    /// 导入项目中的元素时替换的扩展名，和 `resolvePath` 相同
+    extensions: IndexMap<String, String>,
+    /// resolver 标记的未解析标识符，即没有绑定的自由引用
+    unresolved_mark: Mark,
+    used_members: IndexSet<Atom>,
+    used_types: IndexSet<Atom>,
+    /// 已有导入的原始 `src`，`resolvePath` 会修改 `src`
+    import_sources: HashMap<Span, String>,
     used_elements: IndexSet<String>,
+    // 路由对象外层使用的元素
+    outer_elements: Vec<Option<IndexSet<String>>>,
+    /// 当前文件中定义的元素
+    local_elements: IndexSet<String>,
+    /// 没有规则的元素，`reportUnknownElements` 时收集
+    unknown_elements: IndexMap<String, Span>,
+}
+
+fn prop_key(prop: &Prop) -> Option<&str> {
+    let key = match prop {
+        Prop::Shorthand(ident) => return Some(ident.sym.as_str()),
+        Prop::KeyValue(KeyValueProp { key, .. }) => key,
+        Prop::Method(method) => &method.key,
+        _ => return None,
+    };
+    match key {
+        PropName::Ident(ident) => Some(ident.sym.as_str()),
+        PropName::Str(key) => key.value.as_str(),
+        _ => None,
+    }
+}
+
+/// `RouteItem`: `{ pattern, content }` or `{ pattern, getContent }`
+fn is_route_item(node: &ObjectLit) -> bool {
+    let keys: Vec<&str> = node
+        .props
+        .iter()
+        .filter_map(|x| x.as_prop().and_then(|prop| prop_key(prop)))
+        .collect();
+    keys.contains(&"pattern") && (keys.contains(&"content") || keys.contains(&"getContent"))
+}
+
+fn gen_loader(modules: &IndexSet<String>) -> Stmt {
+    let mut imports: Vec<Expr> = modules
+        .iter()
+        .map(|src| {
+            quote!(
+                "import($src)" as Expr,
+                src: Expr = Expr::Lit(Lit::Str(src.as_str().into()))
+            )
+        })
+        .collect();
+    if imports.len() == 1 {
+        return quote!("await $expr;" as Stmt, expr: Expr = imports.remove(0));
+    }
+    let arr = Expr::Array(ArrayLit {
+        elems: imports
+            .into_iter()
+            .map(|expr| {
+                Some(ExprOrSpread {
+                    spread: None,
+                    expr: expr.into(),
+                })
+            })
+            .collect(),
+        ..Default::default()
+    });
+    quote!("await Promise.all($arr);" as Stmt, arr: Expr = arr)
+}
+
+fn lazy_function(func: &mut Function, loader: Stmt) -> bool {
+    let Some(body) = &mut func.body else {
+        return false;
+    };
+    if func.is_generator {
+        return false;
+    }
+    func.is_async = true;
+    body.stmts.insert(0, loader);
+    true
+}
+
+fn lazy_arrow(arrow: &mut ArrowExpr, loader: Stmt) {
+    arrow.is_async = true;
+    match &mut *arrow.body {
+        BlockStmtOrExpr::BlockStmt(body) => body.stmts.insert(0, loader),
+        BlockStmtOrExpr::Expr(expr) => {
+            let span = expr.span();
+            let ret = quote!("return $expr;" as Stmt, expr: Expr = *expr.clone());
+            *arrow.body = BlockStmtOrExpr::BlockStmt(BlockStmt {
+                span,
+                stmts: vec![loader, ret],
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/// `content: expr` -> `getContent: async () => { await import(...); return expr; }`
+///
+/// `getContent` 函数会变成异步函数并在开头加载视图模块
+fn lazy_route_item(node: &mut ObjectLit, loader: Stmt) -> bool {
+    let has_get_content = node
+        .props
+        .iter()
+        .any(|x| x.as_prop().and_then(|prop| prop_key(prop)) == Some("getContent"));
+
+    for item in node.props.iter_mut() {
+        let PropOrSpread::Prop(prop) = item else {
+            continue;
+        };
+        let key = prop_key(prop).map(str::to_string);
+        match (key.as_deref(), &mut **prop) {
+            (Some("getContent"), Prop::Method(method)) => {
+                return lazy_function(&mut method.function, loader);
+            }
+            (Some("getContent"), Prop::KeyValue(KeyValueProp { value, .. })) => {
+                return match &mut **value {
+                    Expr::Arrow(arrow) => {
+                        lazy_arrow(arrow, loader);
+                        true
+                    }
+                    Expr::Fn(FnExpr { function, .. }) => lazy_function(function, loader),
+                    _ => false,
+                };
+            }
+            (Some("content"), _) if !has_get_content => {
+                let span = prop.span();
+                let content = match &**prop {
+                    Prop::Shorthand(ident) => Expr::Ident(ident.clone()),
+                    Prop::KeyValue(KeyValueProp { value, .. }) => *value.clone(),
+                    _ => return false,
+                };
+                let mut arrow = quote!("() => $content" as Expr, content: Expr = content);
+                if let Some(arrow) = arrow.as_mut_arrow() {
+                    lazy_arrow(arrow, loader);
+                    arrow.span = span;
+                }
+                **prop = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident("getContent".into()),
+                    value: arrow.into(),
+                });
+                return true;
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// `HookVisitor` 不访问类型，单独收集类型位置（类型引用、`implements`、泛型参数）的自由引用
+struct TypeCollector {
+    unresolved_mark: Mark,
+    used: IndexSet<Atom>,
+}
+
+impl TypeCollector {
+    fn insert(&mut self, ident: &Ident) {
+        if ident.ctxt.outer() == self.unresolved_mark {
+            self.used.insert(ident.sym.clone());
+        }
+    }
+}
+
+impl Visit for TypeCollector {
+    fn visit_ts_type_ref(&mut self, node: &TsTypeRef) {
+        let mut name = &node.type_name;
+        while let TsEntityName::TsQualifiedName(qualified) = name {
+            name = &qualified.left;
+        }
+        if let TsEntityName::Ident(ident) = name {
+            self.insert(ident);
+        }
+        node.visit_children_with(self);
+    }
+
+    fn visit_ts_expr_with_type_args(&mut self, node: &TsExprWithTypeArgs) {
+        if let Expr::Ident(ident) = &*node.expr {
+            self.insert(ident);
+        }
+        node.visit_children_with(self);
+    }
 }
 
 impl TransformVisitor {
+    fn resolve_element(&self, tag: &str) -> Option<String> {
+        self.config
+            .tag_config
+            .iter()
+            .find(|RegexStringPair { regex, .. }| regex.is_match(tag))
+            .map(|RegexStringPair { regex, path }| regex.replace(tag, path).to_string())
+    }
+
+    /// 项目中的元素使用相对路径导入，当前文件定义的元素不导入
+    fn resolve_registry_element(&self, tag: &str) -> Option<String> {
+        let file = self.registry.get(tag)?;
+        let importer = get_cwd().join(self.filename.as_ref()?);
+        if self.local_elements.contains(tag) || file == &importer {
+            return None;
+        }
+        let relative = diff_paths(
+            converting_to_unix_path(file),
+            converting_to_unix_path(importer.parent()?),
+        )?;
+        let relative = replace_extension(relative.to_str()?, &self.extensions);
+        if relative.starts_with('.') {
+            Some(relative)
+        } else {
+            Some(format!("./{relative}"))
+        }
+    }
+
+    fn resolve_import_element(&self, tag: &str) -> Option<String> {
+        self.resolve_element(tag)
+            .or_else(|| self.resolve_registry_element(tag))
+    }
+
     fn gen_dts(&self, gen_dts: AutoImportDts) {
         let path = match gen_dts {
             AutoImportDts::Src(true) => "src/auto-import.d.ts".into(),
@@ -58,21 +510,38 @@ impl TransformVisitor {
             return;
         }
 
-        // https://github.com/swc-project/swc/discussions/4997
-        let path = Path::new("/cwd").join(path);
+        let path = get_cwd().join(path);
 
-        if path.exists() {
+        let content = self.dts_content();
+        if fs::read_to_string(&path).is_ok_and(|old| old == content) {
             return;
         }
+        if let Err(err) = fs::write(&path, content) {
+            HANDLER.with(|handler| {
+                handler.warn(&format!(
+                    "failed to create auto import dts `{}`: {err}",
+                    path.display()
+                ))
+            });
+        }
+    }
 
+    /// 排序后输出，配置不变时内容不变
+    fn dts_content(&self) -> String {
         let mut import_list: Vec<String> = vec![
             "// AUTOMATICALLY GENERATED, DO NOT MODIFY MANUALLY.".into(),
-            "// DELETING WILL REGENERATE".into(),
+            "// REGENERATED WHEN THE AUTO IMPORT CONFIG CHANGES".into(),
             "".into(),
             "export {}".into(),
+            "".into(),
+            "type AutoImportElement<M> = {".into(),
+            "  [K in keyof M]: M[K] extends abstract new (...args: any) => infer E extends HTMLElement ? E : never;".into(),
+            "}[keyof M];".into(),
+            "".into(),
             "declare global {".into(),
         ];
-        for (local, (imported, pkg)) in &self.config.member_map {
+        let members: BTreeMap<_, _> = self.config.member_map.exact.iter().collect();
+        for (local, (imported, pkg)) in members {
             let member = imported
                 .as_ref()
                 .map_or(local.clone(), |x| x.as_str().to_string());
@@ -80,149 +549,354 @@ impl TransformVisitor {
                 "  const {local}: typeof import('{pkg}')['{member}'];",
             ));
         }
+        // 全局声明中不能 `export`，每个类型使用别名
+        let types: BTreeMap<_, _> = self.config.type_map.exact.iter().collect();
+        for (local, (imported, pkg)) in types {
+            let member = imported.as_ref().map_or(local.as_str(), |x| x.as_str());
+            import_list.push(match self.config.type_params.get(local) {
+                Some(params) => format!(
+                    "  type {local}<{params}> = import('{pkg}').{member}<{}>;",
+                    type_args(params)
+                ),
+                None => format!("  type {local} = import('{pkg}').{member};"),
+            });
+        }
+        let elements: BTreeMap<String, String> = self
+            .config
+            .tag_config
+            .iter()
+            .flat_map(|RegexStringPair { regex, .. }| expand_tag_pattern(regex.as_str()))
+            .filter_map(|tag| Some((tag.clone(), self.resolve_element(&tag)?)))
+            .collect();
+        if !elements.is_empty() {
+            import_list.push("  interface HTMLElementTagNameMap {".into());
+            for (tag, module) in elements {
+                import_list.push(format!(
+                    "    '{tag}': AutoImportElement<typeof import('{module}')>;"
+                ));
+            }
+            import_list.push("  }".into());
+        }
         import_list.push("}".into());
-
-        fs::write(path, import_list.join("\n")).expect("create dts error");
+        import_list.join("\n")
     }
 
-    fn inset_used_member(&mut self, ident: &Ident) {
-        self.used_members.insert(ident.to_id());
+    fn insert_used_element(&mut self, tag: String, span: Span) {
+        if self.config.report_unknown_elements && self.resolve_import_element(&tag).is_none() {
+            self.unknown_elements.entry(tag.clone()).or_insert(span);
+        }
+        self.used_elements.insert(tag);
     }
 
-    fn normalize_auto_import_ctxt(&self, ident: &mut Ident) {
-        if self.config.member_map.contains_key(ident.sym.as_str())
-            && !self.defined_members.contains(&ident.to_id())
-        {
-            ident.ctxt = SyntaxContext::empty();
+    fn report_unknown_elements(&self) {
+        for (span, msg) in self.unknown_element_warnings() {
+            HANDLER.with(|handler| handler.struct_span_warn(span, &msg).emit());
         }
     }
 
-    fn inset_defined_member(&mut self, ident: &Ident) {
-        self.defined_members.insert(ident.to_id());
+    /// 建议来自没有 `*` 的元素规则和当前文件中定义的元素
+    fn unknown_element_warnings(&self) -> Vec<(Span, String)> {
+        let unknown_elements: Vec<_> = self
+            .unknown_elements
+            .iter()
+            .filter(|(tag, _)| !self.local_elements.contains(*tag))
+            .collect();
+        if unknown_elements.is_empty() {
+            return vec![];
+        }
+        let known_elements: Vec<String> = self
+            .config
+            .tag_config
+            .iter()
+            .flat_map(|RegexStringPair { regex, .. }| expand_tag_pattern(regex.as_str()))
+            .chain(self.registry.keys().cloned())
+            .chain(self.local_elements.iter().cloned())
+            .collect();
+        unknown_elements
+            .into_iter()
+            .map(|(tag, span)| {
+                let mut msg = format!("no auto import rule for custom element `<{tag}>`");
+                if let Some(similar) = did_you_mean(tag, known_elements.iter().map(String::as_str))
+                {
+                    msg.push_str(&format!(", did you mean `<{similar}>`?"));
+                }
+                (*span, msg)
+            })
+            .collect()
     }
-}
-
-impl VisitMut for TransformVisitor {
-    noop_visit_mut_type!();
 
-    fn visit_mut_import_specifier(&mut self, node: &mut ImportSpecifier) {
-        self.inset_defined_member(node.local());
+    /// 只有自由引用才会自动导入
+    fn inset_used_member(&mut self, ident: &mut Ident) {
+        if ident.ctxt.outer() != self.unresolved_mark {
+            return;
+        }
+        if self.config.member_map.contains_key(ident.sym.as_str()) {
+            ident.ctxt = SyntaxContext::empty();
+        }
+        self.used_members.insert(ident.sym.clone());
     }
 
-    fn visit_mut_ident(&mut self, node: &mut Ident) {
-        self.normalize_auto_import_ctxt(node);
-        self.inset_used_member(node);
+    fn import_source<'a>(&'a self, decl: &'a ImportDecl) -> Option<&'a str> {
+        match self.import_sources.get(&decl.span) {
+            Some(src) => Some(src),
+            None => decl.src.value.as_str(),
+        }
     }
 
-    fn visit_mut_tagged_tpl(&mut self, node: &mut TaggedTpl) {
-        node.visit_mut_children_with(self);
+    /// 可以添加具名导入的已有导入，类型优先使用 `import type`
+    fn find_import<'a>(
+        &self,
+        items: &'a mut [ModuleItem],
+        pkg: &str,
+        type_only: bool,
+    ) -> Option<&'a mut ImportDecl> {
+        let mergeable = |decl: &ImportDecl, decl_type_only: bool| {
+            decl.type_only == decl_type_only
+                && decl.with.is_none()
+                && decl.phase == ImportPhase::Evaluation
+                && !decl
+                    .specifiers
+                    .iter()
+                    .any(|x| x.is_namespace() || (decl_type_only && x.is_default()))
+                && self.import_source(decl) == Some(pkg)
+        };
+        let index = items
+            .iter()
+            .position(|item| {
+                type_only
+                    && item
+                        .as_module_decl()
+                        .and_then(|x| x.as_import())
+                        .is_some_and(|decl| mergeable(decl, true))
+            })
+            .or_else(|| {
+                items.iter().position(|item| {
+                    item.as_module_decl()
+                        .and_then(|x| x.as_import())
+                        .is_some_and(|decl| mergeable(decl, false))
+                })
+            })?;
+        items[index].as_mut_module_decl()?.as_mut_import()
+    }
+}
 
-        for ele in &node.tpl.quasis {
-            for cap in CUSTOM_ELEMENT_REGEX.captures_iter(ele.raw.as_str()) {
-                self.used_elements.insert(cap["tag"].to_string());
+impl Hook for TransformVisitor {
+    fn enter_import_decl(&mut self, node: &mut ImportDecl, _: &mut HookContext) -> bool {
+        if let Some(src) = node.src.value.as_str() {
+            if !node.span.is_dummy() {
+                self.import_sources.insert(node.span, src.to_string());
             }
         }
+        true
     }
 
-    fn visit_mut_class_decl(&mut self, node: &mut ClassDecl) {
-        node.visit_mut_children_with(self);
-
-        self.inset_defined_member(&node.ident);
+    fn enter_module_items(&mut self, node: &mut Vec<ModuleItem>, _: &mut HookContext) -> bool {
+        let mut collector = TypeCollector {
+            unresolved_mark: self.unresolved_mark,
+            used: Default::default(),
+        };
+        node.visit_with(&mut collector);
+        self.used_types = collector.used;
+        true
     }
 
-    fn visit_mut_class_expr(&mut self, node: &mut ClassExpr) {
-        node.visit_mut_children_with(self);
+    fn enter_ident(&mut self, node: &mut Ident, _: &mut HookContext) -> bool {
+        self.inset_used_member(node);
+        true
+    }
 
-        if let Some(ident) = &node.ident {
-            self.inset_defined_member(ident);
-        }
+    // 路由中的元素使用动态导入，首次渲染时才加载
+    fn enter_object_lit(&mut self, node: &mut ObjectLit, _: &mut HookContext) -> bool {
+        let outer_elements = if self.lazy_view && is_route_item(node) {
+            Some(mem::take(&mut self.used_elements))
+        } else {
+            None
+        };
+        self.outer_elements.push(outer_elements);
+        true
     }
 
-    fn visit_mut_fn_decl(&mut self, node: &mut FnDecl) {
-        node.visit_mut_children_with(self);
+    fn exit_object_lit(&mut self, node: &mut ObjectLit, ctx: &mut HookContext) {
+        let Some(Some(outer_elements)) = self.outer_elements.pop() else {
+            return;
+        };
+        let route_elements = mem::replace(&mut self.used_elements, outer_elements);
 
-        self.inset_defined_member(&node.ident);
-    }
+        let modules: IndexSet<String> = route_elements
+            .iter()
+            .filter_map(|tag| self.resolve_import_element(tag))
+            .collect();
 
-    fn visit_mut_fn_expr(&mut self, node: &mut FnExpr) {
-        node.visit_mut_children_with(self);
+        let rewritten = !modules.is_empty() && {
+            let mut loader = gen_loader(&modules);
+            ctx.visit(&mut loader);
+            lazy_route_item(node, loader)
+        };
+        if !rewritten {
+            // 不能改写的路由还是使用静态导入
+            self.used_elements.extend(route_elements);
+            return;
+        }
 
-        if let Some(ident) = &node.ident {
-            self.inset_defined_member(ident);
+        for tag in &route_elements {
+            if let Some(module) = self.resolve_import_element(tag) {
+                debug!("lazy import `{module}` for <{tag}>");
+                record(|report| {
+                    report.imported_elements.push(ImportedElement {
+                        tag: tag.clone(),
+                        module,
+                        lazy: true,
+                    })
+                });
+            }
         }
     }
 
-    fn visit_mut_var_declarator(&mut self, node: &mut VarDeclarator) {
-        node.visit_mut_children_with(self);
+    fn exit_tagged_tpl(&mut self, node: &mut TaggedTpl, _: &mut HookContext) {
+        let quasis = node.tpl.quasis.iter().map(|x| x.raw.as_str());
+        for tag in scan_custom_elements(quasis) {
+            let lo = node.tpl.quasis[tag.quasi].span.lo + BytePos(tag.offset as u32);
+            let span = Span::new(lo, lo + BytePos(tag.name.len() as u32));
+            self.insert_used_element(tag.name, span);
+        }
+    }
 
-        if let Some(ident) = &node.name.as_ident() {
-            self.inset_defined_member(ident);
+    // `@customElement('dy-x')`
+    fn exit_class(&mut self, node: &mut Class, _: &mut HookContext) {
+        self.local_elements.extend(custom_element_tag(node));
+    }
+
+    // `document.createElement('dy-x')`, `customElements.whenDefined('dy-x')`,
+    // `customElements.define('dy-x', ...)`
+    fn exit_call_expr(&mut self, node: &mut CallExpr, _: &mut HookContext) {
+        let Some(member) = node.callee.as_expr().and_then(|x| x.as_member()) else {
+            return;
+        };
+        let (Some(obj), Some(prop)) = (member.obj.as_ident(), member.prop.as_ident()) else {
+            return;
+        };
+        let Some((tag, span)) = tag_arg(node) else {
+            return;
+        };
+        match (obj.sym.as_str(), prop.sym.as_str()) {
+            ("document", "createElement") | ("customElements", "whenDefined") => {
+                self.insert_used_element(tag, span);
+            }
+            ("customElements", "define") => {
+                self.local_elements.insert(tag);
+            }
+            _ => {}
         }
     }
 
     // https://swc.rs/docs/plugin/ecmascript/cheatsheet#inserting-new-nodes
     // 只处理模块
-    fn visit_mut_module_items(&mut self, node: &mut Vec<ModuleItem>) {
-        node.visit_mut_children_with(self);
+    fn exit_module_items(&mut self, node: &mut Vec<ModuleItem>, ctx: &mut HookContext) {
+        self.report_unknown_elements();
 
         let first_item_span = node.first().map(|item| item.span()).unwrap_or(DUMMY_SP);
         let mut out: Vec<ImportDecl> = vec![];
-        let mut available_import: IndexMap<String, IndexMap<&Atom, Option<&Atom>>> =
-            IndexMap::new();
+        let mut available_import: IndexMap<String, IndexMap<Atom, Option<Atom>>> = IndexMap::new();
 
-        for id in &self.used_members {
-            if !self.defined_members.contains(id) {
-                let res = self.config.member_map.get(id.0.as_str());
-                if let Some((imported, pkg)) = res {
-                    let set = available_import.entry(pkg.into()).or_default();
-                    set.insert(&id.0, imported.as_ref());
-                }
+        for sym in &self.used_members {
+            if let Some((imported, pkg)) = self.config.member_map.get(sym.as_str()) {
+                let set = available_import.entry(pkg).or_default();
+                set.insert(sym.clone(), imported);
             }
         }
 
-        for (pkg, set) in available_import {
+        // 只在类型位置使用的
+        let mut available_type_import: IndexMap<String, IndexMap<Atom, Option<Atom>>> =
+            IndexMap::new();
+        for sym in &self.used_types {
+            if available_import.values().any(|set| set.contains_key(sym)) {
+                continue;
+            }
+            if let Some((imported, pkg)) = self.config.type_map.get(sym.as_str()) {
+                let set = available_type_import.entry(pkg).or_default();
+                set.insert(sym.clone(), imported);
+            }
+        }
+
+        let imports = available_import
+            .into_iter()
+            .map(|x| (x, false))
+            .chain(available_type_import.into_iter().map(|x| (x, true)));
+        for ((pkg, set), type_only) in imports {
             let mut specifiers: Vec<ImportSpecifier> = vec![];
             for (member_as, member) in set {
+                debug!("auto import `{member_as}` from `{pkg}`");
+                record(|report| {
+                    report.imported_members.push(ImportedMember {
+                        local: member_as.to_string(),
+                        imported: member.as_ref().map(|x| x.to_string()),
+                        package: pkg.clone(),
+                        type_only,
+                    })
+                });
                 specifiers.push(ImportSpecifier::Named(ImportNamedSpecifier {
                     // Use empty syntax context so imports survive decorator downlevel
                     // transforms that rebind identifiers with a different ctxt (2023-11).
                     local: Ident::new(member_as.clone(), DUMMY_SP, SyntaxContext::empty()),
                     span: DUMMY_SP,
-                    imported: member.map(|x| ModuleExportName::Ident(x.clone().into())),
+                    imported: member.map(|x| ModuleExportName::Ident(x.into())),
                     is_type_only: false,
                 }));
             }
+            // 添加到同一个包的已有导入中，不改变导入顺序
+            if let Some(decl) = self.find_import(node, &pkg, type_only) {
+                for specifier in &mut specifiers {
+                    if let ImportSpecifier::Named(named) = specifier {
+                        named.is_type_only = type_only && !decl.type_only;
+                    }
+                }
+                decl.specifiers.extend(specifiers);
+                continue;
+            }
             out.push(ImportDecl {
                 specifiers,
-                // 也许可以支持替换：'@mantou/gem/{:pascal:}' + ColorPicker ->
-                // '@mantou/gem/ColorPicker'
                 src: Box::new(Str::from(pkg)),
                 span: DUMMY_SP,
-                type_only: false,
+                type_only,
                 with: None,
                 phase: Default::default(),
             });
         }
 
         for tag in &self.used_elements {
-            for RegexStringPair { regex, path } in &self.config.tag_config {
-                if regex.is_match(tag) {
-                    out.push(ImportDecl {
-                        specifiers: vec![],
-                        src: Box::new(Str::from(regex.replace(tag, path))),
-                        span: DUMMY_SP,
-                        type_only: false,
-                        with: None,
-                        phase: Default::default(),
-                    });
-                    break;
+            if let Some(src) = self.resolve_import_element(tag) {
+                let imported = node.iter().any(|item| {
+                    item.as_module_decl()
+                        .and_then(|x| x.as_import())
+                        .is_some_and(|decl| self.import_source(decl) == Some(&src))
+                });
+                if imported {
+                    continue;
                 }
+                debug!("auto import `{src}` for <{tag}>");
+                record(|report| {
+                    report.imported_elements.push(ImportedElement {
+                        tag: tag.clone(),
+                        module: src.clone(),
+                        lazy: false,
+                    })
+                });
+                out.push(ImportDecl {
+                    specifiers: vec![],
+                    src: Box::new(Str::from(src)),
+                    span: DUMMY_SP,
+                    type_only: false,
+                    with: None,
+                    phase: Default::default(),
+                });
             }
         }
 
         if let Some(first) = out.first_mut() {
             first.span = first_item_span;
         }
+        // resolvePath 等后面的转换需要处理生成的导入
+        out.iter_mut().for_each(|decl| ctx.visit(decl));
 
         node.splice(
             0..0,
@@ -233,9 +907,29 @@ impl VisitMut for TransformVisitor {
     }
 }
 
-pub fn import_transform(auto_import: AutoImport, gen_dts: AutoImportDts) -> impl VisitMut {
+/// `unresolved_mark`: the mark passed to `resolver`, only unresolved identifiers are imported,
+/// `filename` is used by `registry` elements
+/// `extensions`: `resolvePath.extensions`
+pub fn import_hook(
+    auto_import: AutoImport,
+    gen_dts: AutoImportDts,
+    lazy_view: bool,
+    unresolved_mark: Mark,
+    filename: Option<String>,
+    extensions: Option<IndexMap<String, String>>,
+) -> impl Hook {
+    let config = get_config(auto_import);
     let visitor = TransformVisitor {
-        config: get_config(auto_import),
+        registry: config
+            .registry
+            .as_deref()
+            .map(load_registry)
+            .unwrap_or_default(),
+        config,
+        lazy_view,
+        unresolved_mark,
+        filename,
+        extensions: extension_map(extensions),
         ..Default::default()
     };
 
@@ -244,16 +938,64 @@ pub fn import_transform(auto_import: AutoImport, gen_dts: AutoImportDts) -> impl
     visitor
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub fn import_transform(
+    auto_import: AutoImport,
+    gen_dts: AutoImportDts,
+    lazy_view: bool,
+    unresolved_mark: Mark,
+    filename: Option<String>,
+    extensions: Option<IndexMap<String, String>>,
+) -> impl VisitMut {
+    HookVisitor::new(vec![Box::new(import_hook(
+        auto_import,
+        gen_dts,
+        lazy_view,
+        unresolved_mark,
+        filename,
+        extensions,
+    ))])
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct AutoImportContent {
-    pub extends: Option<String>,
-    pub members: Option<HashMap<String, Vec<MemberOrMemberAs>>>,
+    /// `gem`, config file path or package (resolved with `exports`)
+    pub extends: Option<AutoImportExtends>,
+    pub members: Option<IndexMap<String, Vec<MemberOrMemberAs>>>,
+    /// Only used in type positions, imported with `import type`
+    pub types: Option<IndexMap<String, Vec<MemberOrMemberAs>>>,
     pub elements: Option<IndexMap<String, IndexMap<String, String>>>,
+    /// Warn on custom elements that no `elements` rule covers
+    pub report_unknown_elements: Option<bool>,
+    /// Directory scanned for `@customElement` classes, or a JSON file of tag -> file
+    /// (paths relative to the JSON file), relative to cwd; elements are imported with relative
+    /// paths and `resolvePath.extensions`, the scan is cached until a directory in it changes
+    pub registry: Option<String>,
+    /// Packages used first when they provide the same member
+    pub priority: Option<Vec<String>>,
+    /// Members (and types) not auto imported, only for this config and its `extends`
+    pub exclude: Option<Vec<String>>,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum AutoImportExtends {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl AutoImportExtends {
+    fn entries(&self) -> &[String] {
+        match self {
+            AutoImportExtends::One(entry) => std::slice::from_ref(entry),
+            AutoImportExtends::Many(entries) => entries,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum AutoImport {
     Gem(bool),
     CustomContent(AutoImportContent),
@@ -278,79 +1020,379 @@ impl Default for AutoImportDts {
     }
 }
 
-fn merge_content(
-    content: AutoImportContent,
-    mut root: Vec<AutoImportContent>,
-) -> Vec<AutoImportContent> {
-    let extends = content.extends.clone();
-    root.push(content);
-
-    if let Some(extends) = extends {
-        if extends == "gem" {
-            return merge_content(get_config_content(AutoImport::Gem(true)), root);
-        } else {
-            let resolver = Resolver::new()
+/// `exports` 中使用的条件
+const CONFIG_CONDITIONS: [&str; 3] = ["gem", "import", "require"];
+
+#[derive(Default)]
+struct ConfigLoader {
+    /// extended config files
+    files: Vec<PathBuf>,
+    /// 解析失败的 `extends` 可能的位置，创建后需要重新加载
+    unresolved: Vec<PathBuf>,
+    /// 被继承的配置中的成员
+    scoped_exclude: ScopedExclude,
+    /// 正在加载的配置，用于检测循环继承
+    stack: Vec<String>,
+    warnings: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl ConfigLoader {
+    /// 不在 `HANDLER` 中时（例如单元测试）只保留
+    fn emit(&self) {
+        if !HANDLER.is_set() {
+            return;
+        }
+        HANDLER.with(|handler| {
+            self.warnings.iter().for_each(|msg| handler.warn(msg));
+            self.errors.iter().for_each(|msg| handler.err(msg));
+        });
+    }
+
+    /// 相对路径相对于 `basedir`，包使用 `exports` 解析
+    fn resolve(entry: &str, basedir: &Path) -> Option<PathBuf> {
+        resolve_package_exports(entry, basedir, &CONFIG_CONDITIONS).or_else(|| {
+            Resolver::new()
                 .with_extensions(["json"])
-                .with_basedir(env::current_dir().expect("get current dir error"));
-            if let Ok(full_path) = resolver.resolve(&extends) {
-                if let Ok(json_str) = fs::read_to_string(full_path) {
-                    if let Ok(json) = serde_json::from_str::<AutoImportContent>(&json_str) {
-                        return merge_content(json, root);
-                    }
+                .with_basedir(basedir.to_path_buf())
+                .resolve(entry)
+                .ok()
+        })
+    }
+
+    /// `entry`、`entry.json`，包还包括各级 `node_modules` 中的 `package.json`
+    fn unresolved_paths(entry: &str, basedir: &Path) -> Vec<PathBuf> {
+        let with_json = |path: PathBuf| [PathBuf::from(format!("{}.json", path.display())), path];
+        let Some((name, _)) = split_package_specifier(entry) else {
+            return with_json(basedir.join(entry)).into();
+        };
+        basedir
+            .ancestors()
+            .flat_map(|dir| {
+                let dir = dir.join("node_modules");
+                let [json, path] = with_json(dir.join(entry));
+                [json, path, dir.join(name).join("package.json")]
+            })
+            .collect()
+    }
+
+    /// 按继承顺序添加到 `chain`，后面的覆盖前面的
+    fn merge(
+        &mut self,
+        content: AutoImportContent,
+        basedir: &Path,
+        chain: &mut Vec<AutoImportContent>,
+    ) {
+        let extends = content.extends.clone();
+        let start = chain.len();
+        chain.push(content);
+
+        for entry in extends.iter().flat_map(|x| x.entries()) {
+            if entry == "gem" {
+                chain.push(self.load(AutoImport::Gem(true)));
+                continue;
+            }
+            let Some(full_path) = Self::resolve(entry, basedir) else {
+                self.unresolved
+                    .extend(Self::unresolved_paths(entry, basedir));
+                self.warnings.push(format!(
+                    "cannot resolve auto import config `{entry}` from `{}`",
+                    basedir.display()
+                ));
+                continue;
+            };
+            let key = fs::canonicalize(&full_path)
+                .unwrap_or(full_path.clone())
+                .display()
+                .to_string();
+            if self.stack.contains(&key) {
+                let cycle: Vec<&str> = self
+                    .stack
+                    .iter()
+                    .skip_while(|x| **x != key)
+                    .chain([&key])
+                    .map(String::as_str)
+                    .collect();
+                self.errors.push(format!(
+                    "circular `extends` in auto import config: {}",
+                    cycle.join(" -> ")
+                ));
+                continue;
+            }
+            self.files.push(full_path.clone());
+            let json = match fs::read_to_string(&full_path) {
+                Ok(json) => json,
+                Err(err) => {
+                    self.warnings.push(format!(
+                        "failed to read auto import config `{}`: {err}",
+                        full_path.display()
+                    ));
+                    continue;
                 }
+            };
+            match serde_json::from_str::<AutoImportContent>(&json) {
+                Ok(content) => {
+                    let dir = full_path.parent().unwrap_or(basedir).to_path_buf();
+                    self.stack.push(key);
+                    self.merge(content, &dir, chain);
+                    self.stack.pop();
+                }
+                Err(err) => self.warnings.push(format!(
+                    "invalid auto import config `{}`: {err}",
+                    full_path.display()
+                )),
             }
         }
+
+        // `exclude` 只作用于自己和继承的配置
+        let exclude = chain[start].exclude.clone().unwrap_or_default();
+        for lv in &mut chain[start + 1..] {
+            lv.exclude
+                .get_or_insert_with(Vec::new)
+                .extend(exclude.iter().cloned());
+        }
     }
 
-    root
-}
+    fn load(&mut self, config: AutoImport) -> AutoImportContent {
+        match config {
+            AutoImport::Gem(_) => {
+                let content: &str = include_str!("../auto-import.json");
+                serde_json::from_str::<AutoImportContent>(content).expect("invalid json")
+            }
+            AutoImport::CustomContent(content) => {
+                let mut chain = vec![];
+                let cwd = get_cwd();
+                self.merge(content, &cwd, &mut chain);
 
-fn get_config_content(config: AutoImport) -> AutoImportContent {
-    match config {
-        AutoImport::Gem(_) => {
-            let content: &str = include_str!("../auto-import.json");
-            serde_json::from_str::<AutoImportContent>(content).expect("invalid json")
+                // 根配置的 `exclude` 作用于所有成员，其他配置的只作用于自己和继承的成员
+                for lv in &chain[1..] {
+                    let exclude = lv.exclude.clone().unwrap_or_default();
+                    if exclude.is_empty() {
+                        continue;
+                    }
+                    for (package, import_vec) in lv.members.iter().chain(&lv.types).flatten() {
+                        for member in import_vec {
+                            let (local, _) = split_type_params(member.local());
+                            self.scoped_exclude
+                                .insert((package.clone(), local.into()), exclude.clone());
+                        }
+                    }
+                }
+
+                // 同一个包的成员逐个合并，近的配置优先，不同包的冲突在 `build_member_map` 中检测
+                let mut members = IndexMap::default();
+                let mut types = IndexMap::default();
+                for lv in chain.iter_mut().rev() {
+                    merge_members(&mut members, lv.members.take());
+                    merge_members(&mut types, lv.types.take());
+                }
+
+                let mut elements = IndexMap::default();
+                let mut report_unknown_elements = None;
+                let mut registry = None;
+                let mut priority = None;
+                let exclude = chain[0].exclude.clone();
+                for lv in chain {
+                    elements.extend(lv.elements.unwrap_or_default());
+                    report_unknown_elements =
+                        lv.report_unknown_elements.or(report_unknown_elements);
+                    registry = lv.registry.or(registry);
+                    // 根配置的优先
+                    priority = priority.or(lv.priority);
+                }
+
+                AutoImportContent {
+                    extends: None,
+                    elements: Some(elements),
+                    members: Some(members),
+                    types: Some(types),
+                    report_unknown_elements,
+                    registry,
+                    priority,
+                    exclude,
+                }
+            }
         }
-        AutoImport::CustomContent(content) => {
-            let chain = merge_content(content, vec![]);
+    }
+}
 
-            let mut elements = IndexMap::default();
-            let mut members = HashMap::default();
-            for lv in chain {
-                elements.extend(lv.elements.unwrap_or_default());
-                members.extend(lv.members.unwrap_or_default());
+/// 相同 local 的成员替换已有的，`members` 来自更近的配置
+fn merge_members(
+    target: &mut IndexMap<String, Vec<MemberOrMemberAs>>,
+    members: Option<IndexMap<String, Vec<MemberOrMemberAs>>>,
+) {
+    for (package, import_vec) in members.into_iter().flatten() {
+        let list = target.entry(package).or_default();
+        for member in import_vec {
+            let (local, _) = split_type_params(member.local());
+            match list
+                .iter_mut()
+                .find(|x| split_type_params(x.local()).0 == local)
+            {
+                Some(existing) => *existing = member,
+                None => list.push(member),
             }
+        }
+    }
+}
 
-            AutoImportContent {
-                extends: None,
-                elements: Some(elements),
-                members: Some(members),
+fn get_mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+struct CachedConfig {
+    /// extended files (and paths of unresolved `extends`) and their mtime
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    config: Arc<AutoImportConfig>,
+}
+
+/// config content json -> config
+static CONFIG_CACHE: Lazy<Mutex<HashMap<String, CachedConfig>>> = Lazy::new(Default::default);
+
+fn get_config(auto_import: AutoImport) -> Arc<AutoImportConfig> {
+    let key = serde_json::to_string(&auto_import).unwrap_or_default();
+    if let Ok(cache) = CONFIG_CACHE.lock() {
+        if let Some(cached) = cache.get(&key) {
+            if cached
+                .files
+                .iter()
+                .all(|(path, mtime)| &get_mtime(path) == mtime)
+            {
+                return cached.config.clone();
             }
         }
     }
+
+    let mut loader = ConfigLoader::default();
+    let content = loader.load(auto_import);
+    let config = Arc::new(build_config(
+        content,
+        &loader.scoped_exclude,
+        &mut loader.warnings,
+    ));
+    loader.emit();
+    if let Ok(mut cache) = CONFIG_CACHE.lock() {
+        let files = loader
+            .files
+            .into_iter()
+            .chain(loader.unresolved)
+            .map(|path| {
+                let mtime = get_mtime(&path);
+                (path, mtime)
+            })
+            .collect();
+        cache.insert(
+            key,
+            CachedConfig {
+                files,
+                config: config.clone(),
+            },
+        );
+    }
+    config
 }
 
-fn get_config(auto_import: AutoImport) -> AutoImportConfig {
-    // TODO: use cache
-    let content = get_config_content(auto_import);
-    let mut member_map = HashMap::new();
+/// 多个包提供同一个成员时使用 `priority` 中靠前的包，都不在 `priority` 中时后面的覆盖前面的并警告
+fn build_member_map(
+    members: Option<IndexMap<String, Vec<MemberOrMemberAs>>>,
+    priority: &[String],
+    exclude: &[String],
+    scoped_exclude: &ScopedExclude,
+    warnings: &mut Vec<String>,
+) -> MemberMap {
+    let mut member_map = MemberMap::default();
+    // local -> 配置中的包名
+    let mut owners: HashMap<String, String> = HashMap::new();
+    let rank = |package: &str| {
+        priority
+            .iter()
+            .position(|x| x == package)
+            .unwrap_or(usize::MAX)
+    };
 
-    for (package, import_vec) in &content.members.unwrap_or_default() {
+    for (package, import_vec) in &members.unwrap_or_default() {
         for member in import_vec {
-            match member {
-                MemberOrMemberAs::Member(name) => {
-                    member_map.insert(name.into(), (None, package.into()));
-                }
+            let (imported, local) = match member {
+                MemberOrMemberAs::Member(name) => (None, name),
                 MemberOrMemberAs::MemberAs([name, member_as]) => {
-                    member_map.insert(
-                        member_as.into(),
-                        (Some(name.clone().into()), package.into()),
-                    );
+                    (Some(name.as_str().into()), member_as)
+                }
+            };
+            let exclude: Vec<String> = exclude
+                .iter()
+                .chain(
+                    scoped_exclude
+                        .get(&(package.clone(), local.clone()))
+                        .into_iter()
+                        .flatten(),
+                )
+                .cloned()
+                .collect();
+            if exclude.contains(local) {
+                continue;
+            }
+            if let Some(owner) = owners.get(local).filter(|owner| *owner != package) {
+                match rank(package).cmp(&rank(owner)) {
+                    Ordering::Greater => continue,
+                    Ordering::Less => {}
+                    Ordering::Equal => warnings.push(format!(
+                        "auto import member `{local}` is provided by both `{owner}` and `{package}`, \
+                         `{package}` is used, set `priority` or `exclude` to choose"
+                    )),
+                }
+            }
+            owners.insert(local.clone(), package.clone());
+            if local.contains('*') {
+                let regex = format!("^{}$", regex::escape(local).replace("\\*", "(.+)"));
+                member_map.patterns.retain(|x| x.regex.as_str() != regex);
+                if let Ok(regex) = Regex::new(&regex) {
+                    member_map.patterns.push(MemberPattern {
+                        regex,
+                        imported,
+                        package: package.clone(),
+                        exclude,
+                    });
                 }
+            } else {
+                member_map
+                    .exact
+                    .insert(local.clone(), (imported, expand_package(package, local)));
             }
         }
     }
 
+    member_map
+}
+
+fn build_config(
+    content: AutoImportContent,
+    scoped_exclude: &ScopedExclude,
+    warnings: &mut Vec<String>,
+) -> AutoImportConfig {
+    let priority = content.priority.unwrap_or_default();
+    let exclude = content.exclude.unwrap_or_default();
+    let member_map = build_member_map(
+        content.members,
+        &priority,
+        &exclude,
+        scoped_exclude,
+        warnings,
+    );
+    let mut type_params = HashMap::new();
+    let types = content.types.map(|types| {
+        types
+            .into_iter()
+            .map(|(package, import_vec)| {
+                let import_vec = import_vec
+                    .into_iter()
+                    .map(|member| take_type_params(member, &mut type_params))
+                    .collect();
+                (package, import_vec)
+            })
+            .collect()
+    });
+    let type_map = build_member_map(types, &priority, &exclude, scoped_exclude, warnings);
+
     let mut tag_config = Vec::new();
 
     for (package, import_map) in content.elements.unwrap_or_default() {
@@ -366,12 +1408,20 @@ fn get_config(auto_import: AutoImport) -> AutoImportConfig {
 
     AutoImportConfig {
         member_map,
+        type_map,
+        type_params,
         tag_config,
+        report_unknown_elements: content.report_unknown_elements.unwrap_or_default(),
+        registry: content.registry.map(|path| get_cwd().join(path)),
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::env;
+
+    use swc_common::GLOBALS;
+
     use super::*;
 
     #[test]
@@ -379,7 +1429,8 @@ mod tests {
         assert_eq!(
             format!(
                 "{:?}",
-                get_config_content(AutoImport::Gem(true))
+                ConfigLoader::default()
+                    .load(AutoImport::Gem(true))
                     .elements
                     .unwrap_or_default()
                     .get("duoyun-ui")
@@ -395,16 +1446,297 @@ mod tests {
         assert_eq!(
             format!(
                 "{:?}",
-                get_config_content(AutoImport::CustomContent(
-                    serde_json::from_str::<AutoImportContent>(r#"{"extends":"gem"}"#).unwrap()
-                ))
-                .elements
-                .unwrap_or_default()
-                .get("duoyun-ui")
-                .unwrap()
-                .keys()
+                ConfigLoader::default()
+                    .load(AutoImport::CustomContent(
+                        serde_json::from_str::<AutoImportContent>(r#"{"extends":["gem"]}"#)
+                            .unwrap()
+                    ))
+                    .elements
+                    .unwrap_or_default()
+                    .get("duoyun-ui")
+                    .unwrap()
+                    .keys()
             ),
             r#"["dy-pat-*", "dy-light-route", "dy-active-link", "dy-(input|form|avatar|radio|checkbox|collapse|tab)-*", "dy-*"]"#
         )
     }
+
+    #[test]
+    fn should_detect_member_conflicts() {
+        let content = |extra: &str| {
+            serde_json::from_str::<AutoImportContent>(&format!(
+                r#"{{"members":{{"a":["X","Y"],"b":["X","Z","Z"]}}{extra}}}"#
+            ))
+            .unwrap()
+        };
+
+        let mut warnings = vec![];
+        let config = build_config(content(""), &Default::default(), &mut warnings);
+        assert_eq!(config.member_map.get("X"), Some((None, "b".into())));
+        assert_eq!(
+            warnings,
+            vec!["auto import member `X` is provided by both `a` and `b`, `b` is used, set `priority` or `exclude` to choose"]
+        );
+
+        let mut warnings = vec![];
+        let config = build_config(
+            content(r#","priority":["a"],"exclude":["Z"]"#),
+            &Default::default(),
+            &mut warnings,
+        );
+        assert!(warnings.is_empty());
+        assert_eq!(config.member_map.get("X"), Some((None, "a".into())));
+        assert!(config.member_map.contains_key("Y"));
+        assert!(!config.member_map.contains_key("Z"));
+    }
+
+    #[test]
+    fn should_load_package_config_and_detect_cycle() {
+        let dir = env::temp_dir().join(format!("gem-auto-import-extends-{}", std::process::id()));
+        let pkg = dir.join("node_modules/preset");
+        fs::create_dir_all(&pkg).unwrap();
+        fs::write(
+            pkg.join("package.json"),
+            r#"{"exports":{"./auto-import":{"gem":"./gem.json","default":"./other.json"}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            pkg.join("gem.json"),
+            r#"{"extends":"./base","members":{"preset":["preset"]}}"#,
+        )
+        .unwrap();
+        fs::write(
+            pkg.join("base.json"),
+            r#"{"extends":"preset/auto-import","members":{"base":["base"]}}"#,
+        )
+        .unwrap();
+
+        let mut loader = ConfigLoader::default();
+        let content = AutoImportContent {
+            extends: Some(AutoImportExtends::Many(vec![
+                "preset/auto-import".into(),
+                "./missing".into(),
+            ])),
+            ..Default::default()
+        };
+        let mut chain = vec![];
+        loader.merge(content, &dir, &mut chain);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let members: Vec<_> = chain
+            .iter()
+            .flat_map(|x| x.members.iter().flatten())
+            .collect();
+        assert_eq!(members.len(), 2);
+        assert_eq!(loader.files.len(), 2);
+        assert_eq!(loader.errors.len(), 1);
+        assert!(loader.errors[0].starts_with("circular `extends` in auto import config"));
+        assert_eq!(loader.warnings.len(), 1);
+        assert!(loader.warnings[0].starts_with("cannot resolve auto import config `./missing`"));
+    }
+
+    #[test]
+    fn should_scope_exclude_and_priority() {
+        let dir = env::temp_dir().join(format!("gem-auto-import-exclude-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("base.json"),
+            r#"{"members":{"a":["X","Y"],"icons/{:kebab:}":[["default","Icon*"]]},"exclude":["Y","Z","IconBar"],"priority":["b"]}"#,
+        )
+        .unwrap();
+
+        let mut loader = ConfigLoader::default();
+        let content = serde_json::from_str::<AutoImportContent>(&format!(
+            r#"{{"extends":"{}","members":{{"b":["X","Z"]}},"exclude":["IconFoo"],"priority":["a"]}}"#,
+            dir.join("base.json").display()
+        ))
+        .unwrap();
+        let content = loader.load(AutoImport::CustomContent(content));
+        let config = build_config(content, &loader.scoped_exclude, &mut loader.warnings);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(loader.warnings.is_empty());
+        assert_eq!(config.member_map.get("X"), Some((None, "a".into())));
+        assert!(!config.member_map.contains_key("Y"));
+        assert_eq!(config.member_map.get("Z"), Some((None, "b".into())));
+        assert!(!config.member_map.contains_key("IconFoo"));
+        assert!(!config.member_map.contains_key("IconBar"));
+        assert!(config.member_map.contains_key("IconBaz"));
+    }
+
+    #[test]
+    fn should_merge_members_of_extends() {
+        let dir = env::temp_dir().join(format!("gem-auto-import-merge-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("base.json"),
+            r#"{"members":{"a":[["x","X"],"Y"],"icons/a/{:kebab:}":[["default","Icon*"]]}}"#,
+        )
+        .unwrap();
+
+        let mut loader = ConfigLoader::default();
+        let content = serde_json::from_str::<AutoImportContent>(&format!(
+            r#"{{"extends":"{}","members":{{"a":[["x2","X"],"W"],"icons/b/{{:kebab:}}":[["default","Icon*"]]}}}}"#,
+            dir.join("base.json").display()
+        ))
+        .unwrap();
+        let content = loader.load(AutoImport::CustomContent(content));
+        let config = build_config(content, &loader.scoped_exclude, &mut loader.warnings);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            config.member_map.get("X"),
+            Some((Some("x2".into()), "a".into()))
+        );
+        assert_eq!(config.member_map.get("Y"), Some((None, "a".into())));
+        assert_eq!(config.member_map.get("W"), Some((None, "a".into())));
+        assert_eq!(
+            config.member_map.get("IconFoo"),
+            Some((Some("default".into()), "icons/b/foo".into()))
+        );
+        assert_eq!(
+            loader.warnings,
+            vec!["auto import member `Icon*` is provided by both `icons/a/{:kebab:}` and `icons/b/{:kebab:}`, `icons/b/{:kebab:}` is used, set `priority` or `exclude` to choose"]
+        );
+    }
+
+    #[test]
+    fn should_expand_package_template() {
+        assert_eq!(
+            expand_package("@mantou/gem/{:kebab:}", "ColorPicker"),
+            "@mantou/gem/color-picker"
+        );
+        assert_eq!(
+            expand_package("icons/{:pascal:}", "arrow_left2"),
+            "icons/ArrowLeft2"
+        );
+        assert_eq!(
+            expand_package("icons/{:camel:}", "HTMLElement"),
+            "icons/htmlElement"
+        );
+    }
+
+    #[test]
+    fn should_match_wildcard_member() {
+        let members = serde_json::from_str(r#"{"icons/{:kebab:}":[["default","Icon*"]]}"#).unwrap();
+        let member_map = build_member_map(
+            Some(members),
+            &[],
+            &["IconFoo".into()],
+            &Default::default(),
+            &mut vec![],
+        );
+        assert_eq!(
+            member_map.get("IconArrowLeft"),
+            Some((Some("default".into()), "icons/arrow-left".into()))
+        );
+        assert_eq!(member_map.get("Icon"), None);
+        assert_eq!(member_map.get("IconFoo"), None);
+        assert!(!member_map.contains_key("IconFoo"));
+    }
+
+    #[test]
+    fn should_regenerate_dts() {
+        let file = env::temp_dir().join(format!("gem-auto-import-{}.d.ts", std::process::id()));
+        fs::write(&file, "stale").unwrap();
+        let visitor = GLOBALS.set(&Default::default(), || TransformVisitor {
+            config: get_config(AutoImport::Gem(true)),
+            ..Default::default()
+        });
+        visitor.gen_dts(AutoImportDts::CustomPath(
+            file.to_string_lossy().to_string(),
+        ));
+        let content = fs::read_to_string(&file).unwrap();
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(content, visitor.dts_content());
+        assert!(content.contains(
+            "  const GemElement: typeof import('@mantou/gem')['GemElement'];\n  const TemplateResult:"
+        ));
+        assert!(content.contains(
+            "    'gem-link': AutoImportElement<typeof import('@mantou/gem/elements/link')>;"
+        ));
+        assert!(content.contains(
+            "    'dy-light-route': AutoImportElement<typeof import('duoyun-ui/elements/route')>;"
+        ));
+        assert!(!content.contains("'dy-pat-"));
+    }
+
+    #[test]
+    fn should_declare_global_types() {
+        let visitor = GLOBALS.set(&Default::default(), || TransformVisitor {
+            config: get_config(AutoImport::CustomContent(
+                serde_json::from_str::<AutoImportContent>(
+                    r#"{"types":{"pkg":["Plain","Box<T extends object = {}>",["default","Dict<K, V = Record<K, any>>"]]}}"#,
+                )
+                .unwrap(),
+            )),
+            ..Default::default()
+        });
+        assert_eq!(
+            visitor.dts_content().split("declare global {\n").nth(1),
+            Some(
+                "  type Box<T extends object = {}> = import('pkg').Box<T>;\n  \
+                 type Dict<K, V = Record<K, any>> = import('pkg').default<K, V>;\n  \
+                 type Plain = import('pkg').Plain;\n}"
+            )
+        );
+        assert_eq!(
+            visitor.config.type_map.get("Box"),
+            Some((None, "pkg".into()))
+        );
+    }
+
+    #[test]
+    fn should_suggest_unknown_elements() {
+        let mut visitor = GLOBALS.set(&Default::default(), || TransformVisitor {
+            config: get_config(AutoImport::CustomContent(AutoImportContent {
+                extends: Some(AutoImportExtends::One("gem".into())),
+                report_unknown_elements: Some(true),
+                ..Default::default()
+            })),
+            ..Default::default()
+        });
+        for tag in ["gem-lnk", "my-elemnt", "foo-bar", "gem-link", "my-element"] {
+            visitor.insert_used_element(tag.into(), DUMMY_SP);
+        }
+        visitor.local_elements.insert("my-element".into());
+
+        let warnings: Vec<_> = visitor
+            .unknown_element_warnings()
+            .into_iter()
+            .map(|(_, msg)| msg)
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "no auto import rule for custom element `<gem-lnk>`, did you mean `<gem-link>`?",
+                "no auto import rule for custom element `<my-elemnt>`, did you mean `<my-element>`?",
+                "no auto import rule for custom element `<foo-bar>`",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_cache_config() {
+        let file = env::temp_dir().join(format!("gem-auto-import-{}.json", std::process::id()));
+        fs::write(&file, r#"{"members":{"pkg":["member"]}}"#).unwrap();
+        let auto_import = AutoImport::CustomContent(AutoImportContent {
+            extends: Some(AutoImportExtends::One(file.to_string_lossy().to_string())),
+            ..Default::default()
+        });
+
+        let config = get_config(auto_import.clone());
+        assert!(config.member_map.contains_key("member"));
+        assert!(Arc::ptr_eq(&config, &get_config(auto_import.clone())));
+
+        fs::remove_file(&file).unwrap();
+        let config = get_config(auto_import.clone());
+        assert!(!config.member_map.contains_key("member"));
+
+        fs::write(&file, r#"{"members":{"pkg":["member"]}}"#).unwrap();
+        let config = get_config(auto_import);
+        fs::remove_file(&file).unwrap();
+        assert!(config.member_map.contains_key("member"));
+    }
 }
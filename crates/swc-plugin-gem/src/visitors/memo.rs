@@ -1,8 +1,7 @@
-use swc_common::DUMMY_SP;
-use swc_core::{
-    atoms::Atom,
-    ecma::visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
-};
+use std::mem;
+
+use swc_common::Span;
+use swc_core::{atoms::Atom, ecma::visit::VisitMut};
 use swc_ecma_ast::{
     ArrowExpr, AssignExpr, AssignOp, AssignTarget, BlockStmt, BlockStmtOrExpr, CallExpr, Callee,
     Class, ClassDecl, ClassMember, ClassProp, Decorator, Expr, ExprOrSpread, ExprStmt, Function,
@@ -10,16 +9,21 @@ use swc_ecma_ast::{
     PropName, SimpleAssignTarget, Stmt, ThisExpr,
 };
 
+use super::hook::{Hook, HookContext, HookVisitor};
+
 #[derive(Default)]
 struct TransformVisitor {
-    private_props: Vec<(Atom, Vec<Decorator>, String)>,
+    private_props: Vec<(Atom, Vec<Decorator>, String, Span)>,
 
     current_class_name: Option<Ident>,
     class_static_dep_fn: Vec<ClassMember>,
+    // 下一个进入的类属于 `ClassDecl`
+    enter_class_decl: bool,
+    class_is_decl: Vec<bool>,
 }
 
 impl TransformVisitor {
-    fn is_memo_getter(&mut self, node: &mut PrivateMethod) -> bool {
+    fn is_memo_getter(&self, node: &PrivateMethod) -> bool {
         if node.kind != MethodKind::Getter {
             return false;
         }
@@ -44,6 +48,7 @@ impl TransformVisitor {
         let class_name = self.current_class_name.as_ref().unwrap();
 
         decorators.iter_mut().enumerate().for_each(|(idx, x)| {
+            let decorator_span = x.span;
             if let Some(call_expr) = x.expr.as_mut_call() {
                 if let Callee::Expr(b) = &call_expr.callee {
                     if let Some(ident) = b.as_ident() {
@@ -60,12 +65,14 @@ impl TransformVisitor {
 
                 let first_arg = *call_expr.args.drain(0..1).next().unwrap().expr;
                 if let Expr::Arrow(arrow_expr) = first_arg {
+                    let span = arrow_expr.span;
                     let prop = format!("_dep_fn_{idx}");
                     // 忽略了箭头函数的 `this` 绑定，模块中类成员装饰器参数中的 `this`
                     // 会指向模块 正常情况下都不会使用
                     // `this`，所以忽略也无所谓
                     self.class_static_dep_fn
                         .push(ClassMember::ClassProp(ClassProp {
+                            span: decorator_span,
                             is_static: true,
                             key: PropName::Ident(prop.clone().into()),
                             value: Some(Expr::Arrow(arrow_expr).into()),
@@ -75,11 +82,13 @@ impl TransformVisitor {
                     call_expr.args.push(ExprOrSpread {
                         spread: None,
                         expr: Expr::Arrow(ArrowExpr {
+                            span,
                             params: vec![Pat::Ident("i".into())],
                             body: BlockStmtOrExpr::Expr(Box::new(Expr::Call(CallExpr {
+                                span,
                                 callee: Callee::Expr(
                                     Expr::Member(MemberExpr {
-                                        span: DUMMY_SP,
+                                        span,
                                         obj: Expr::Ident(class_name.clone()).into(),
                                         prop: MemberProp::Ident(prop.into()),
                                     })
@@ -102,46 +111,50 @@ impl TransformVisitor {
     }
 }
 
-impl VisitMut for TransformVisitor {
-    noop_visit_mut_type!();
-
-    fn visit_mut_class(&mut self, node: &mut Class) {
-        node.visit_mut_children_with(self);
+impl Hook for TransformVisitor {
+    fn enter_class(&mut self, _: &mut Class, _: &mut HookContext) -> bool {
+        let is_decl = mem::take(&mut self.enter_class_decl);
+        self.class_is_decl.push(is_decl);
+        true
+    }
 
-        while let Some((prop, decorators, getter_name)) = self.private_props.pop() {
-            node.body.push(ClassMember::PrivateMethod(PrivateMethod {
-                span: DUMMY_SP,
+    // 在 super class 之前插入，和 class 成员一起交给后面的转换
+    fn exit_class_members(&mut self, node: &mut Vec<ClassMember>, ctx: &mut HookContext) {
+        // 生成的成员使用 memo getter 的 span
+        while let Some((prop, decorators, getter_name, span)) = self.private_props.pop() {
+            let mut method = ClassMember::PrivateMethod(PrivateMethod {
+                span,
                 kind: MethodKind::Method,
                 key: PrivateName {
-                    span: DUMMY_SP,
+                    span,
                     name: format!("_{getter_name}").into(),
                 },
                 function: Box::new(Function {
-                    span: DUMMY_SP,
+                    span,
                     params: vec![],
                     decorators,
                     body: Some(BlockStmt {
-                        span: DUMMY_SP,
+                        span,
                         stmts: vec![Stmt::Expr(ExprStmt {
-                            span: DUMMY_SP,
+                            span,
                             expr: Box::new(Expr::Assign(AssignExpr {
-                                span: DUMMY_SP,
+                                span,
                                 op: AssignOp::Assign,
                                 left: AssignTarget::Simple(SimpleAssignTarget::Member(
                                     MemberExpr {
-                                        span: DUMMY_SP,
-                                        obj: ThisExpr { span: DUMMY_SP }.into(),
+                                        span,
+                                        obj: ThisExpr { span }.into(),
                                         prop: MemberProp::PrivateName(PrivateName {
-                                            span: DUMMY_SP,
+                                            span,
                                             name: prop.clone(),
                                         }),
                                     },
                                 )),
                                 right: Box::new(Expr::Member(MemberExpr {
-                                    span: DUMMY_SP,
-                                    obj: ThisExpr { span: DUMMY_SP }.into(),
+                                    span,
+                                    obj: ThisExpr { span }.into(),
                                     prop: MemberProp::PrivateName(PrivateName {
-                                        span: DUMMY_SP,
+                                        span,
                                         name: getter_name.as_str().into(),
                                     }),
                                 })),
@@ -152,49 +165,64 @@ impl VisitMut for TransformVisitor {
                     ..Default::default()
                 }),
                 ..Default::default()
-            }));
-            node.body.push(ClassMember::PrivateProp(PrivateProp {
+            });
+            let mut prop = ClassMember::PrivateProp(PrivateProp {
+                span,
                 key: PrivateName {
-                    span: DUMMY_SP,
+                    span,
                     name: prop.as_str().into(),
                 },
                 ..Default::default()
-            }));
+            });
+            ctx.visit(&mut method);
+            ctx.visit(&mut prop);
+            node.push(method);
+            node.push(prop);
+        }
+
+        if self.class_is_decl.pop() == Some(true) {
+            self.current_class_name = None;
+            for mut dep_fn in self.class_static_dep_fn.drain(..) {
+                ctx.visit(&mut dep_fn);
+                node.push(dep_fn);
+            }
         }
     }
 
-    fn visit_mut_private_method(&mut self, node: &mut PrivateMethod) {
+    fn enter_private_method(&mut self, node: &mut PrivateMethod, _: &mut HookContext) -> bool {
         if self.is_memo_getter(node) {
             let name = node.key.name.clone();
             let getter_name = format!("_{name}");
 
             node.key = PrivateName {
-                span: DUMMY_SP,
+                span: node.key.span,
                 name: getter_name.clone().into(),
             };
 
             let decorators = node.function.decorators.drain(..).collect();
 
             self.private_props
-                .push((name.clone(), decorators, getter_name));
+                .push((name.clone(), decorators, getter_name, node.span));
         }
+        false
     }
 
     // 修复 https://github.com/swc-project/swc/issues/9565
     // 1. 修改所有类成员的装饰器，将原装饰器函数参数转为静态方法存在
     //    `members_decorators` 中
-    // 2. 将 `members_decorators` 中的静态方法插入到 class 中
-    fn visit_mut_class_decl(&mut self, node: &mut ClassDecl) {
+    // 2. 将 `members_decorators` 中的静态方法插入到 class 中（在 `exit_class`
+    //    中插入，后面的转换能看到）
+    fn enter_class_decl(&mut self, node: &mut ClassDecl, _: &mut HookContext) -> bool {
         self.current_class_name = Some(node.ident.clone());
-        node.visit_mut_children_with(self);
-        self.current_class_name = None;
-        node.class.body.append(&mut self.class_static_dep_fn);
+        self.enter_class_decl = true;
+        true
     }
 
-    fn visit_mut_class_member(&mut self, node: &mut ClassMember) {
-        node.visit_mut_children_with(self);
-
+    // 在访问成员前移出装饰器参数，后面的转换在插入的位置看到它
+    fn enter_class_member(&mut self, node: &mut ClassMember, _: &mut HookContext) -> bool {
         match node {
+            // memo getter 的装饰器会移动到生成的方法上
+            ClassMember::PrivateMethod(private_method) if self.is_memo_getter(private_method) => {}
             ClassMember::PrivateMethod(private_method) => {
                 self.process_decorator(&mut private_method.function.decorators);
             }
@@ -209,9 +237,14 @@ impl VisitMut for TransformVisitor {
             }
             _ => {}
         }
+        true
     }
 }
 
-pub fn memo_transform() -> impl VisitMut {
+pub fn memo_hook() -> impl Hook {
     TransformVisitor::default()
 }
+
+pub fn memo_transform() -> impl VisitMut {
+    HookVisitor::new(vec![Box::new(memo_hook())])
+}
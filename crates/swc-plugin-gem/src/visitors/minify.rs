@@ -1,9 +1,10 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
-use swc_common::DUMMY_SP;
-use swc_core::ecma::visit::{noop_visit_mut_type, VisitMut, VisitMutWith};
+use swc_core::ecma::visit::VisitMut;
 use swc_ecma_ast::{Callee, KeyValueProp, TaggedTpl, Tpl, TplElement};
 
+use super::hook::{Hook, HookContext, HookVisitor};
+
 fn should_keep_expr_boundary_space(ch: char) -> bool {
     ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || ch == '%' || ch == ')'
 }
@@ -172,7 +173,7 @@ fn minify_css_style_tpl(tpl: &Tpl) -> Tpl {
             }
 
             TplElement {
-                span: DUMMY_SP,
+                span: quasi.span,
                 tail: quasi.tail,
                 cooked: None,
                 raw: removed_space.into(),
@@ -181,7 +182,7 @@ fn minify_css_style_tpl(tpl: &Tpl) -> Tpl {
         .collect();
 
     Tpl {
-        span: DUMMY_SP,
+        span: tpl.span,
         exprs: tpl.exprs.clone(),
         quasis,
     }
@@ -192,7 +193,7 @@ static TAG_COMMENT_REG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<!--.*?-->")
 
 fn minify_html_tpl(tpl: &Tpl) -> Tpl {
     Tpl {
-        span: DUMMY_SP,
+        span: tpl.span,
         exprs: tpl.exprs.clone(),
         quasis: tpl
             .quasis
@@ -201,7 +202,7 @@ fn minify_html_tpl(tpl: &Tpl) -> Tpl {
                 let removed_comment = TAG_COMMENT_REG.replace_all(x.raw.as_str(), "");
                 let removed_between = TAG_BETWEEN_REG.replace_all(&removed_comment, "> <");
                 TplElement {
-                    span: DUMMY_SP,
+                    span: x.span,
                     tail: x.tail,
                     cooked: None,
                     raw: removed_between.into(),
@@ -214,12 +215,8 @@ fn minify_html_tpl(tpl: &Tpl) -> Tpl {
 #[derive(Default)]
 struct TransformVisitor {}
 
-impl VisitMut for TransformVisitor {
-    noop_visit_mut_type!();
-
-    fn visit_mut_tagged_tpl(&mut self, node: &mut TaggedTpl) {
-        node.visit_mut_children_with(self);
-
+impl Hook for TransformVisitor {
+    fn exit_tagged_tpl(&mut self, node: &mut TaggedTpl, _: &mut HookContext) {
         if let Some(ident) = node.tag.as_ident() {
             let tag_fn = ident.sym.as_str();
             if tag_fn == "css" || tag_fn == "styled" {
@@ -231,23 +228,27 @@ impl VisitMut for TransformVisitor {
         }
     }
 
-    fn visit_mut_callee(&mut self, node: &mut Callee) {
+    fn enter_callee(&mut self, node: &mut Callee, _: &mut HookContext) -> bool {
         if let Callee::Expr(expr) = &node {
             if let Some(ident) = expr.as_ident() {
-                if ident.sym.as_str() == "css" {
-                    node.visit_mut_children_with(self);
-                }
+                return ident.sym.as_str() == "css";
             }
         }
+        false
     }
 
-    fn visit_mut_key_value_prop(&mut self, node: &mut KeyValueProp) {
+    fn enter_key_value_prop(&mut self, node: &mut KeyValueProp, _: &mut HookContext) -> bool {
         if let Some(tpl) = node.value.as_tpl() {
             node.value = minify_css_style_tpl(tpl).into();
         }
+        false
     }
 }
 
-pub fn minify_transform() -> impl VisitMut {
+pub fn minify_hook() -> impl Hook {
     TransformVisitor::default()
 }
+
+pub fn minify_transform() -> impl VisitMut {
+    HookVisitor::new(vec![Box::new(minify_hook())])
+}
@@ -1,4 +1,5 @@
 pub mod hmr;
+pub mod hook;
 pub mod import;
 pub mod memo;
 pub mod minify;
@@ -1,70 +1,766 @@
-use std::{env, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs, mem,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
 
+use indexmap::IndexMap;
 use node_resolve::Resolver;
+use once_cell::sync::Lazy;
 use pathdiff::diff_paths;
-use swc_core::ecma::visit::{noop_visit_mut_type, VisitMut};
-use swc_ecma_ast::{CallExpr, Callee, ExprOrSpread, ImportDecl, Lit, Str};
+use serde::{Deserialize, Serialize};
+use swc_common::{errors::HANDLER, Span};
+use swc_core::ecma::visit::VisitMut;
+use swc_ecma_ast::{
+    CallExpr, Callee, ExportAll, ExportSpecifier, Expr, ExprOrSpread, ImportDecl, ImportSpecifier,
+    Lit, MetaPropKind, ModuleItem, NamedExport, NewExpr, Str,
+};
 use typed_path::{Utf8Path, Utf8UnixEncoding, Utf8WindowsEncoding};
 
-fn converting_to_unix_path(path: &Path) -> String {
+use super::hook::{Hook, HookContext, HookVisitor};
+use crate::{
+    report::record,
+    resolve::{
+        resolve_package_exports, resolve_package_imports, split_package_specifier, TsConfigPaths,
+    },
+    suggest::did_you_mean,
+};
+
+const DEFAULT_CONDITIONS: [&str; 2] = ["browser", "import"];
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ResolvePathOptions {
+    /// Import map JSON file (relative to cwd), bare specifiers are rewritten with its `imports`
+    /// (exact or longest `/` prefix, `scopes` are not supported)
+    pub import_map: Option<String>,
+    /// Keep bare specifiers and add them to this import map JSON file (relative to cwd),
+    /// values come from `importMap` or `node_modules` (`/node_modules/...` relative to cwd,
+    /// with the `extensions`, `publicPath` and `hash` rewrites); entries of each source file are
+    /// cached in `node_modules/.cache/swc-plugin-gem`, so the result does not depend on
+    /// the transform order or process
+    pub emit_import_map: Option<String>,
+    /// package.json `exports` / `imports` conditions, default `["browser", "import"]`,
+    /// `default` always matches
+    pub conditions: Option<Vec<String>>,
+    /// tsconfig (relative to cwd) used for `paths` / `baseUrl`, default `tsconfig.json`;
+    /// `extends` is not followed, aliases are resolved before `importMap` / `emitImportMap`
+    pub tsconfig: Option<String>,
+    /// Rewrite the final extension of resolved files, merged into
+    /// `{ ".ts": ".js", ".tsx": ".js", ".mts": ".mjs", ".cts": ".cjs" }`;
+    /// TS ESM specifiers (`./x.js`, `./x.mjs`, `./x.cjs`) also find their TS sources
+    pub extensions: Option<IndexMap<String, String>>,
+    /// Error on relative or absolute specifiers that cannot be resolved
+    pub strict: bool,
+    /// Specifiers containing one of these are not checked by `strict`, e.g. `?url`
+    pub allow: Option<Vec<String>>,
+    /// Resolved files become URLs under this base, e.g. `/static/app/`
+    pub public_path: Option<String>,
+    /// Directory (relative to cwd) mapped to `publicPath`, default cwd
+    pub public_root: Option<String>,
+    /// Append `?v=<content hash>` (FNV-1a, 8 hex digits) of the resolved file, only its own
+    /// content: when `b.ts` changes, `a.js` imports the new `b.js?v=...` but keeps its own URL,
+    /// so immutable caching is only safe for files that import nothing
+    pub hash: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ResolvePath {
+    Enabled(bool),
+    Options(Box<ResolvePathOptions>),
+}
+
+impl Default for ResolvePath {
+    fn default() -> Self {
+        ResolvePath::Enabled(false)
+    }
+}
+
+impl ResolvePath {
+    pub fn options(&self) -> Option<ResolvePathOptions> {
+        match self {
+            ResolvePath::Enabled(false) => None,
+            ResolvePath::Enabled(true) => Some(Default::default()),
+            ResolvePath::Options(options) => Some(*options.clone()),
+        }
+    }
+}
+
+/// https://github.com/WICG/import-maps，不支持 `scopes`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+pub struct ImportMap {
+    #[serde(default)]
+    pub imports: IndexMap<String, String>,
+}
+
+impl ImportMap {
+    fn read(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read import map `{}`: {err}", path.display()))?;
+        serde_json::from_str(&content)
+            .map_err(|err| format!("invalid import map `{}`: {err}", path.display()))
+    }
+
+    /// 完全匹配，或者以 `/` 结尾的最长前缀
+    pub fn resolve(&self, specifier: &str) -> Option<String> {
+        if let Some(target) = self.imports.get(specifier) {
+            return Some(target.clone());
+        }
+        self.imports
+            .iter()
+            .filter(|(key, _)| key.ends_with('/') && specifier.starts_with(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(key, target)| format!("{target}{}", &specifier[key.len()..]))
+    }
+}
+
+/// `emitImportMap` 中一个源文件使用的裸模块
+#[derive(Deserialize, Serialize)]
+struct ImportMapPart {
+    file: String,
+    imports: BTreeMap<String, String>,
+}
+
+/// 每个源文件的裸模块保存在 cwd 的缓存目录中，按 `emitImportMap` 文件区分，
+/// 多个进程（loader worker、wasm 插件实例）共享
+pub fn import_map_parts_dir(emit: &Path) -> PathBuf {
+    get_cwd()
+        .join("node_modules/.cache/swc-plugin-gem/import-map")
+        .join(content_hash(converting_to_unix_path(emit).as_bytes()))
+}
+
+/// 所有源文件使用的裸模块，已经删除的源文件除外
+fn read_import_map_parts(dir: &Path) -> BTreeMap<String, String> {
+    let cwd = get_cwd();
+    let mut parts: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    parts.sort();
+    parts
+        .iter()
+        .filter_map(|path| {
+            serde_json::from_str::<ImportMapPart>(&fs::read_to_string(path).ok()?).ok()
+        })
+        .filter(|part| cwd.join(&part.file).exists())
+        .flat_map(|part| part.imports)
+        .collect()
+}
+
+/// 先写入临时文件再重命名，其他进程不会读到写了一半的文件
+fn write_atomic(path: &Path, content: &str, key: &str) -> std::io::Result<()> {
+    let tmp = PathBuf::from(format!("{}.{key}.tmp", path.display()));
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)
+}
+
+/// 文件 -> (mtime, 内容哈希)
+static HASH_CACHE: Lazy<Mutex<HashMap<PathBuf, (Option<SystemTime>, String)>>> =
+    Lazy::new(Default::default);
+
+/// FNV-1a，8 位十六进制，不同的编译器版本结果相同
+fn content_hash(content: &[u8]) -> String {
+    let hash = content.iter().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x01000193)
+    });
+    format!("{hash:08x}")
+}
+
+/// 按 mtime 缓存，只包括文件自身的内容，不包括它导入的文件
+fn file_hash(path: &Path) -> Option<String> {
+    let mtime = fs::metadata(path).and_then(|x| x.modified()).ok();
+    let mut cache = HASH_CACHE.lock().ok()?;
+    match cache.get(path) {
+        Some((cached, hash)) if *cached == mtime => Some(hash.clone()),
+        _ => {
+            let hash = content_hash(&fs::read(path).ok()?);
+            cache.insert(path.to_path_buf(), (mtime, hash.clone()));
+            Some(hash)
+        }
+    }
+}
+
+/// `@mantou/gem`、`lit/decorators.js`，不包括相对路径、`#internal` 和 URL
+fn is_bare_specifier(specifier: &str) -> bool {
+    split_package_specifier(specifier).is_some()
+        && !specifier.starts_with('#')
+        && !specifier.contains(':')
+}
+
+// https://github.com/swc-project/swc/discussions/4997
+pub fn get_cwd() -> PathBuf {
+    if cfg!(target_family = "wasm") {
+        PathBuf::from("/cwd")
+    } else {
+        env::current_dir().unwrap_or_default()
+    }
+}
+
+pub fn converting_to_unix_path(path: &Path) -> String {
     let windows_path = Utf8Path::<Utf8WindowsEncoding>::new(path.to_str().unwrap());
     windows_path.with_encoding::<Utf8UnixEncoding>().to_string()
 }
 
+/// 解析时尝试的扩展名
+const RESOLVE_EXTENSIONS: [&str; 8] = ["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+/// TS ESM 使用编译后的扩展名导入源文件，例如 `./x.js` 导入 `./x.ts`
+const SOURCE_EXTENSIONS: [(&str, &[&str]); 4] = [
+    ("js", &["ts", "tsx"]),
+    ("jsx", &["tsx"]),
+    ("mjs", &["mts"]),
+    ("cjs", &["cts"]),
+];
+
+/// 编译后的扩展名，不带 `.`
+const DEFAULT_EXTENSION_MAP: [(&str, &str); 4] =
+    [("ts", "js"), ("tsx", "js"), ("mts", "mjs"), ("cts", "cjs")];
+
+/// 默认值和 `extensions` 合并，key 和 value 都去掉开头的 `.`
+pub fn extension_map(extensions: Option<IndexMap<String, String>>) -> IndexMap<String, String> {
+    let trim = |ext: &str| ext.trim_start_matches('.').to_string();
+    DEFAULT_EXTENSION_MAP
+        .iter()
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .chain(
+            extensions
+                .unwrap_or_default()
+                .iter()
+                .map(|(from, to)| (trim(from), trim(to))),
+        )
+        .collect()
+}
+
+/// 只替换最后一段路径的扩展名，导入 TypeScript 文件时使用编译后的扩展名
+pub fn replace_extension(path: &str, extensions: &IndexMap<String, String>) -> String {
+    let name_start = path.rfind('/').map_or(0, |index| index + 1);
+    let Some(dot) = path[name_start..]
+        .rfind('.')
+        .map(|index| name_start + index)
+    else {
+        return path.to_string();
+    };
+    match extensions.get(&path[dot + 1..]) {
+        // `.gitignore` 这样的文件名没有扩展名
+        Some(to) if dot > name_start => format!("{}.{to}", &path[..dot]),
+        _ => path.to_string(),
+    }
+}
+
 #[derive(Default)]
 struct TransformVisitor {
     filename: Option<String>,
+    /// 编译前后的扩展名
+    extensions: IndexMap<String, String>,
+    strict: bool,
+    allow: Vec<String>,
+    /// `publicPath`（以 `/` 结尾）和 `publicRoot` 的绝对路径
+    public_path: Option<(String, PathBuf)>,
+    hash: bool,
+    conditions: Vec<String>,
+    tsconfig: Option<Arc<TsConfigPaths>>,
+    import_map: Option<ImportMap>,
+    emit_import_map: Option<PathBuf>,
+    /// 当前文件使用的裸模块和导入映射中的值
+    bare_imports: BTreeMap<String, String>,
 }
 
 impl TransformVisitor {
-    fn resolve_path(&self, origin: &str) -> Str {
-        if let Some(filename) = &self.filename {
-            let cwd = env::current_dir().expect("get current dir error");
-            let full_filename = cwd.join(filename);
-            let dir = full_filename.parent().unwrap();
-            let resolver = Resolver::new()
-                .with_extensions(["ts", "js", ".mjs"])
-                .with_basedir(dir.to_path_buf());
-            if let Ok(ref full_path) = resolver.resolve(origin) {
-                if let Some(relative_path) = diff_paths(
-                    converting_to_unix_path(full_path),
-                    converting_to_unix_path(dir),
-                ) {
-                    if let Some(relative_path) = relative_path.to_str() {
-                        let relative_path = relative_path.replace(".ts", ".js");
-                        if !relative_path.starts_with('.') {
-                            return format!("./{relative_path}").into();
-                        } else {
-                            return relative_path.into();
-                        }
-                    }
+    /// 当前文件所在目录
+    fn basedir(&self) -> Option<PathBuf> {
+        let cwd = get_cwd();
+        Some(match &self.filename {
+            Some(filename) => cwd.join(filename).parent()?.to_path_buf(),
+            None => cwd,
+        })
+    }
+
+    fn resolver(dir: &Path) -> Resolver {
+        Resolver::new()
+            .with_extensions(RESOLVE_EXTENSIONS)
+            .with_basedir(dir.to_path_buf())
+    }
+
+    /// tsconfig `paths` 中的别名，例如 `@/x`、`~/x`
+    fn resolve_alias(&self, origin: &str, dir: &Path) -> Option<PathBuf> {
+        split_package_specifier(origin)?;
+        let resolver = Self::resolver(dir);
+        self.tsconfig
+            .as_ref()?
+            .candidates(origin)
+            .iter()
+            .find_map(|x| resolver.resolve(x.to_str()?).ok())
+    }
+
+    /// 依次使用 package.json `imports`、tsconfig `paths`、package.json `exports`，
+    /// 最后使用 `node_modules` 中的 `main`
+    fn resolve_file(&self, origin: &str, dir: &Path) -> Option<PathBuf> {
+        let conditions: Vec<&str> = self.conditions.iter().map(String::as_str).collect();
+        let resolver = Self::resolver(dir);
+        let resolve = |path: &Path| resolver.resolve(path.to_str()?).ok();
+        if origin.starts_with('#') {
+            return resolve_package_imports(origin, dir, &conditions).and_then(|x| resolve(&x));
+        }
+        if let Some(path) = self.resolve_alias(origin, dir) {
+            return Some(path);
+        }
+        if split_package_specifier(origin).is_some() {
+            if let Some(path) = resolve_package_exports(origin, dir, &conditions) {
+                return resolve(&path);
+            }
+        }
+        resolver.resolve(origin).ok().or_else(|| {
+            let (stem, ext) = origin.rsplit_once('.')?;
+            let (_, sources) = SOURCE_EXTENSIONS.iter().find(|(from, _)| *from == ext)?;
+            sources
+                .iter()
+                .find_map(|source| resolver.resolve(&format!("{stem}.{source}")).ok())
+        })
+    }
+
+    /// `node_modules` 中的模块，导入映射中的值
+    fn resolve_node_modules(&self, specifier: &str) -> Option<String> {
+        let full_path = self.resolve_file(specifier, &self.basedir()?)?;
+        self.file_url(&full_path, None)
+    }
+
+    /// 建议同一目录中名称相近的文件，没有扩展名时也比较去掉扩展名的文件名
+    fn report_unresolved(&self, origin: &str, dir: &Path, span: Span) {
+        let (parent, name) = origin.rsplit_once('/').unwrap_or(("", origin));
+        let search_dir = if origin.starts_with('/') {
+            PathBuf::from(format!("{parent}/"))
+        } else {
+            dir.join(parent)
+        };
+        let has_ext = name.contains('.');
+        let candidates: Vec<String> = fs::read_dir(search_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|file| !file.starts_with('.'))
+            .map(|file| match file.rsplit_once('.') {
+                Some((stem, _)) if !has_ext => stem.to_string(),
+                _ => file,
+            })
+            .collect();
+        let mut msg = format!("cannot resolve `{origin}`");
+        if let Some(similar) = did_you_mean(name, candidates.iter().map(String::as_str)) {
+            msg.push_str(&format!(", did you mean `{parent}/{similar}`?"));
+        }
+        HANDLER.with(|handler| handler.struct_span_err(span, &msg).emit());
+    }
+
+    /// 相对当前目录 `dir` 的路径，或者 `publicPath` 下的 URL（`publicRoot` 外的文件除外），
+    /// `hash` 时添加文件内容的哈希；没有 `dir` 时（导入映射中的值）使用相对 cwd 以 `/` 开头的路径
+    fn file_url(&self, full_path: &Path, dir: Option<&Path>) -> Option<String> {
+        let relative = |base: &Path| {
+            let path = diff_paths(
+                converting_to_unix_path(full_path),
+                converting_to_unix_path(base),
+            )?;
+            Some(replace_extension(path.to_str()?, &self.extensions))
+        };
+        let public_url = self.public_path.as_ref().and_then(|(public_path, root)| {
+            relative(root)
+                .filter(|path| !path.starts_with("../"))
+                .map(|path| format!("{public_path}{path}"))
+        });
+        let mut url = match (public_url, dir) {
+            (Some(url), _) => url,
+            (None, Some(dir)) => {
+                let path = relative(dir)?;
+                if path.starts_with('.') {
+                    path
+                } else {
+                    format!("./{path}")
                 }
             }
+            (None, None) => format!("/{}", relative(&get_cwd())?),
+        };
+        if self.hash {
+            if let Some(hash) = file_hash(full_path) {
+                url.push_str(&format!("?v={hash}"));
+            }
+        }
+        Some(url)
+    }
+
+    fn resolve_path(&mut self, origin: &str, span: Span) -> Str {
+        let dir = self.filename.as_ref().and_then(|_| self.basedir());
+        // 别名不是包，不使用导入映射
+        if let Some(dir) = &dir {
+            if let Some(full_path) = self.resolve_alias(origin, dir) {
+                if let Some(url) = self.file_url(&full_path, Some(dir)) {
+                    return url.into();
+                }
+            }
+        }
+        if is_bare_specifier(origin) {
+            let target = self.import_map.as_ref().and_then(|x| x.resolve(origin));
+            if self.emit_import_map.is_some() {
+                if let Some(target) = target.or_else(|| self.resolve_node_modules(origin)) {
+                    self.bare_imports.insert(origin.into(), target);
+                }
+                return origin.into();
+            }
+            if let Some(target) = target {
+                return target.into();
+            }
+        }
+        if let Some(dir) = dir {
+            if let Some(ref full_path) = self.resolve_file(origin, &dir) {
+                if let Some(url) = self.file_url(full_path, Some(&dir)) {
+                    return url.into();
+                }
+            } else if self.strict
+                && (origin.starts_with("./")
+                    || origin.starts_with("../")
+                    || origin.starts_with('/'))
+                && !self
+                    .allow
+                    .iter()
+                    .any(|allow| origin.contains(allow.as_str()))
+            {
+                self.report_unresolved(origin, &dir, span);
+            }
         }
         origin.into()
     }
 }
 
-impl VisitMut for TransformVisitor {
-    noop_visit_mut_type!();
+/// `import.meta.url`, `import.meta.resolve`
+fn is_import_meta_prop(expr: &Expr, prop: &str) -> bool {
+    expr.as_member().is_some_and(|member| {
+        member
+            .obj
+            .as_meta_prop()
+            .is_some_and(|x| x.kind == MetaPropKind::ImportMeta)
+            && member.prop.as_ident().is_some_and(|x| x.sym == prop)
+    })
+}
+
+/// 第一个参数是字符串时改写
+fn first_str_arg(args: &mut [ExprOrSpread]) -> Option<&mut Str> {
+    match args.first_mut()?.expr.as_mut_lit()? {
+        Lit::Str(source) => Some(source),
+        _ => None,
+    }
+}
+
+/// `import type { X }`、`import { type X }`，可能只有 `.d.ts`，TS 会删除它们
+fn is_type_only_import(node: &ImportDecl) -> bool {
+    node.type_only
+        || (!node.specifiers.is_empty()
+            && node
+                .specifiers
+                .iter()
+                .all(|x| matches!(x, ImportSpecifier::Named(x) if x.is_type_only)))
+}
+
+/// `export type { X } from './x'`、`export { type X } from './x'`
+fn is_type_only_export(node: &NamedExport) -> bool {
+    node.type_only
+        || (!node.specifiers.is_empty()
+            && node
+                .specifiers
+                .iter()
+                .all(|x| matches!(x, ExportSpecifier::Named(x) if x.is_type_only)))
+}
+
+impl TransformVisitor {
+    fn write_import_map(
+        &self,
+        path: &Path,
+        bare_imports: BTreeMap<String, String>,
+    ) -> std::io::Result<()> {
+        let filename = self.filename.clone().unwrap_or_default();
+        let key = content_hash(filename.as_bytes());
+        let dir = import_map_parts_dir(path);
+        let part_path = dir.join(format!("{key}.json"));
+        if bare_imports.is_empty() {
+            if part_path.exists() {
+                fs::remove_file(&part_path)?;
+            }
+        } else {
+            fs::create_dir_all(&dir)?;
+            let part = ImportMapPart {
+                file: filename,
+                imports: bare_imports,
+            };
+            write_atomic(&part_path, &serde_json::to_string(&part)?, &key)?;
+        }
+
+        // 其他进程可能同时写入，写入后再检查一次，最后写入的进程会包含所有源文件
+        for _ in 0..3 {
+            let imports = read_import_map_parts(&dir);
+            let old =
+                ImportMap::read(path).map(|x| x.imports.into_iter().collect::<BTreeMap<_, _>>());
+            if old.is_ok_and(|old| old == imports) {
+                break;
+            }
+            let import_map = ImportMap {
+                imports: imports.into_iter().collect(),
+            };
+            let content = serde_json::to_string_pretty(&import_map)? + "\n";
+            write_atomic(path, &content, &key)?;
+        }
+        Ok(())
+    }
+
+    fn rewrite_src(&mut self, src: &mut Str) {
+        *src = self.resolve_path(src.value.as_str().unwrap_or_default(), src.span);
+    }
+}
+
+impl Hook for TransformVisitor {
+    fn enter_import_decl(&mut self, node: &mut ImportDecl, _: &mut HookContext) -> bool {
+        if !is_type_only_import(node) {
+            self.rewrite_src(&mut node.src);
+        }
+        false
+    }
+
+    // `export { x } from './x'`, `export * as x from './x'`
+    fn enter_named_export(&mut self, node: &mut NamedExport, _: &mut HookContext) -> bool {
+        if is_type_only_export(node) {
+            return false;
+        }
+        if let Some(src) = &mut node.src {
+            self.rewrite_src(src);
+        }
+        false
+    }
 
-    fn visit_mut_import_decl(&mut self, node: &mut ImportDecl) {
-        node.src = self.resolve_path(node.src.value.as_str().unwrap_or_default()).into();
+    // `export * from './x'`
+    fn enter_export_all(&mut self, node: &mut ExportAll, _: &mut HookContext) -> bool {
+        self.rewrite_src(&mut node.src);
+        false
     }
 
-    // 只处理 string 的动态导入
-    fn visit_mut_call_expr(&mut self, node: &mut CallExpr) {
-        if let Callee::Import(_) = node.callee {
-            if let Some(Some(Lit::Str(source))) = node.args.first().map(|e| e.expr.as_lit()) {
-                node.args = vec![ExprOrSpread {
-                    spread: None,
-                    expr: self.resolve_path(source.value.as_str().unwrap_or_default()).into(),
-                }]
+    // `new URL('./x', import.meta.url)`，只处理相对路径，`new URL('x', ...)` 不是包
+    fn enter_new_expr(&mut self, node: &mut NewExpr, _: &mut HookContext) -> bool {
+        let is_url = node.callee.as_ident().is_some_and(|x| x.sym == "URL");
+        let Some(args) = node.args.as_mut().filter(|args| args.len() == 2) else {
+            return true;
+        };
+        if !is_url || !is_import_meta_prop(&args[1].expr, "url") {
+            return true;
+        }
+        if let Some(source) = first_str_arg(args) {
+            let value = source.value.as_str().unwrap_or_default();
+            if value.starts_with("./") || value.starts_with("../") {
+                self.rewrite_src(source);
             }
         }
+        true
+    }
+
+    // 写入所有源文件的并集，和转换顺序、进程无关，文件再次转换时替换它的条目
+    fn exit_module_items(&mut self, _: &mut Vec<ModuleItem>, _: &mut HookContext) {
+        let Some(path) = &self.emit_import_map else {
+            return;
+        };
+        let bare_imports = mem::take(&mut self.bare_imports);
+        record(|report| report.bare_imports = bare_imports.clone());
+        if let Err(err) = self.write_import_map(path, bare_imports) {
+            HANDLER.with(|handler| {
+                handler.warn(&format!(
+                    "failed to write import map `{}`: {err}",
+                    path.display()
+                ))
+            });
+        }
+    }
+
+    // 只处理 string 的动态导入和 `import.meta.resolve`
+    fn enter_call_expr(&mut self, node: &mut CallExpr, _: &mut HookContext) -> bool {
+        let resolvable = match &node.callee {
+            Callee::Import(_) => true,
+            Callee::Expr(callee) => is_import_meta_prop(callee, "resolve"),
+            _ => false,
+        };
+        if resolvable {
+            if let Some(source) = first_str_arg(&mut node.args) {
+                self.rewrite_src(source);
+            }
+        }
+        true
     }
 }
 
-pub fn path_transform(filename: Option<String>) -> impl VisitMut {
-    TransformVisitor { filename }
+pub fn path_hook(filename: Option<String>, options: ResolvePathOptions) -> impl Hook {
+    let cwd = get_cwd();
+    let import_map = options
+        .import_map
+        .map(|path| ImportMap::read(&cwd.join(path)))
+        .and_then(|result| {
+            result
+                .map_err(|err| HANDLER.with(|handler| handler.warn(&err)))
+                .ok()
+        });
+    let conditions = options.conditions.unwrap_or_else(|| {
+        DEFAULT_CONDITIONS
+            .iter()
+            .map(|condition| condition.to_string())
+            .collect()
+    });
+    let tsconfig = TsConfigPaths::read_cached(
+        &cwd.join(options.tsconfig.as_deref().unwrap_or("tsconfig.json")),
+    );
+    TransformVisitor {
+        filename,
+        extensions: extension_map(options.extensions),
+        strict: options.strict,
+        allow: options.allow.unwrap_or_default(),
+        public_path: options.public_path.map(|public_path| {
+            let public_path = if public_path.ends_with('/') {
+                public_path
+            } else {
+                format!("{public_path}/")
+            };
+            (
+                public_path,
+                cwd.join(options.public_root.as_deref().unwrap_or(".")),
+            )
+        }),
+        hash: options.hash,
+        conditions,
+        tsconfig,
+        import_map,
+        emit_import_map: options.emit_import_map.map(|path| cwd.join(path)),
+        ..Default::default()
+    }
+}
+
+pub fn path_transform(filename: Option<String>, options: ResolvePathOptions) -> impl VisitMut {
+    HookVisitor::new(vec![Box::new(path_hook(filename, options))])
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_common::{sync::Lrc, FileName, SourceMap};
+    use swc_core::ecma::{parser::parse_file_as_module, visit::VisitMutWith};
+    use swc_ecma_ast::EsVersion;
+
+    use super::*;
+
+    fn emit_imports(filename: &str, source: &str, emit: &Path) -> Vec<String> {
+        let cm: Lrc<SourceMap> = Default::default();
+        let fm = cm.new_source_file(FileName::Anon.into(), source.to_string());
+        let mut module = parse_file_as_module(
+            &fm,
+            Default::default(),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        module.visit_mut_with(&mut path_transform(
+            Some(filename.into()),
+            ResolvePathOptions {
+                import_map: Some("tests/fixture/path-import-map/import-map.json".into()),
+                emit_import_map: Some(emit.to_string_lossy().to_string()),
+                ..Default::default()
+            },
+        ));
+        ImportMap::read(emit).unwrap().imports.into_keys().collect()
+    }
+
+    /// 删除缓存目录和空的上级目录
+    fn remove_import_map_parts(emit: &Path) {
+        let dir = import_map_parts_dir(emit);
+        fs::remove_dir_all(&dir).unwrap();
+        for dir in dir.ancestors().skip(1).take(4) {
+            if fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn should_emit_imports_of_all_files() {
+        let dir = env::temp_dir().join(format!("gem-emit-import-map-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let emit = dir.join("import-map.json");
+        fs::write(&emit, r#"{"imports":{"stale":"/stale.js"}}"#).unwrap();
+        let [a, b] = ["a.ts", "b.ts"].map(|name| {
+            let file = dir.join(name);
+            fs::write(&file, "").unwrap();
+            file.to_string_lossy().to_string()
+        });
+
+        let first = emit_imports(
+            &a,
+            "import '@mantou/gem'; import '@mantou/gem/lib/store.js';",
+            &emit,
+        );
+        let second = emit_imports(&b, "import '@mantou/gem/elements/link.js';", &emit);
+        let third = emit_imports(&a, "import '@mantou/gem';", &emit);
+        fs::remove_file(&b).unwrap();
+        let fourth = emit_imports(&a, "import '@mantou/gem';", &emit);
+        remove_import_map_parts(&emit);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(first, vec!["@mantou/gem", "@mantou/gem/lib/store.js"]);
+        assert_eq!(
+            second,
+            vec![
+                "@mantou/gem",
+                "@mantou/gem/elements/link.js",
+                "@mantou/gem/lib/store.js"
+            ]
+        );
+        assert_eq!(third, vec!["@mantou/gem", "@mantou/gem/elements/link.js"]);
+        assert_eq!(fourth, vec!["@mantou/gem"]);
+    }
+
+    #[test]
+    fn should_resolve_import_map() {
+        let import_map: ImportMap = serde_json::from_str(
+            r#"{"imports":{"@mantou/gem":"/vendor/gem/index.js","@mantou/gem/":"/vendor/gem/","@mantou/gem/elements/":"https://esm.sh/@mantou/gem/elements/"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            import_map.resolve("@mantou/gem"),
+            Some("/vendor/gem/index.js".into())
+        );
+        assert_eq!(
+            import_map.resolve("@mantou/gem/lib/store.js"),
+            Some("/vendor/gem/lib/store.js".into())
+        );
+        assert_eq!(
+            import_map.resolve("@mantou/gem/elements/link.js"),
+            Some("https://esm.sh/@mantou/gem/elements/link.js".into())
+        );
+        assert_eq!(import_map.resolve("lit"), None);
+        assert!(is_bare_specifier("@mantou/gem"));
+        assert!(!is_bare_specifier("./a.js"));
+        assert!(!is_bare_specifier("https://esm.sh/lit"));
+    }
+
+    #[test]
+    fn should_replace_final_extension() {
+        let extensions = extension_map(Some(IndexMap::from_iter([(
+            ".tsx".to_string(),
+            ".jsx".to_string(),
+        )])));
+        assert_eq!(
+            replace_extension("./types.tsx-utils/x.ts", &extensions),
+            "./types.tsx-utils/x.js"
+        );
+        assert_eq!(
+            replace_extension("../.tsdata/a.mts", &extensions),
+            "../.tsdata/a.mjs"
+        );
+        assert_eq!(replace_extension("./a.tsx", &extensions), "./a.jsx");
+        assert_eq!(replace_extension("./a.json", &extensions), "./a.json");
+        assert_eq!(replace_extension("./.ts", &extensions), "./.ts");
+    }
 }
@@ -1,17 +1,54 @@
+use std::{collections::HashSet, mem};
+
 use once_cell::sync::Lazy;
 use regex::Regex;
+use swc_common::{errors::HANDLER, Span, DUMMY_SP};
 use swc_core::{
-    ecma::visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
+    atoms::Atom,
+    ecma::visit::{Visit, VisitMut, VisitWith},
     quote,
 };
-use swc_ecma_ast::{Ident, ImportDecl, ModuleItem, Str};
+use swc_ecma_ast::{
+    ArrayLit, ArrayPat, Expr, ExprOrSpread, ExprStmt, Ident, ImportDecl, ModuleDecl, ModuleItem,
+    Pat, Stmt, Str, VarDecl, VarDeclKind, VarDeclarator,
+};
+use tracing::debug;
+
+use super::hook::{Hook, HookContext, HookVisitor};
+use crate::report::{record, Preload};
 
 static IMG_REG: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?i)\.(svg|gif|jpe?g|tiff?|a?png|webp|avif|bmp)$").unwrap());
 
 enum AwaitItem {
-    Img(Ident),
-    ArrayBuffer(Ident),
+    Img(Ident, Span),
+    /// 导入语句原来的本地名称，URL 改用不冲突的名称
+    ArrayBuffer(Ident, Span),
+}
+
+/// 模块中出现的所有标识符，用来生成不冲突的名称
+#[derive(Default)]
+struct IdentCollector {
+    used: HashSet<Atom>,
+}
+
+impl Visit for IdentCollector {
+    fn visit_ident(&mut self, node: &Ident) {
+        self.used.insert(node.sym.clone());
+    }
+}
+
+impl IdentCollector {
+    fn unique(&mut self, name: &str) -> Atom {
+        let mut sym: Atom = format!("_{name}").into();
+        let mut i = 1;
+        while self.used.contains(&sym) {
+            sym = format!("_{name}{i}").into();
+            i += 1;
+        }
+        self.used.insert(sym.clone());
+        sym
+    }
 }
 
 #[derive(Default)]
@@ -19,62 +56,177 @@ struct TransformVisitor {
     await_items: Vec<AwaitItem>,
 }
 
-impl VisitMut for TransformVisitor {
-    noop_visit_mut_type!();
-
-    fn visit_mut_import_decl(&mut self, node: &mut ImportDecl) {
+impl Hook for TransformVisitor {
+    fn enter_import_decl(&mut self, node: &mut ImportDecl, _: &mut HookContext) -> bool {
         let value = node.src.value.as_str().unwrap_or_default();
         if let Some((source, prefix)) = value.split_once('?') {
             if prefix != "preload" {
-                return;
+                return false;
             }
-            let ident = node
-                .specifiers
-                .get_mut(0)
-                .expect("preload only allow one specifier")
-                .local_mut();
-            if IMG_REG.is_match(source) {
-                self.await_items.push(AwaitItem::Img(ident.clone()));
+            if node.specifiers.len() != 1 || !node.specifiers[0].is_default() {
+                HANDLER.with(|handler| {
+                    handler
+                        .struct_span_err(
+                            node.span,
+                            "preload only allow one default specifier, e.g. `import src from \
+                             'x.png?preload'`",
+                        )
+                        .emit()
+                });
+                return false;
+            }
+            let ident = node.specifiers[0].local_mut();
+            let is_img = IMG_REG.is_match(source);
+            debug!("preload `{source}`");
+            record(|report| {
+                report.preloads.push(Preload {
+                    source: source.to_string(),
+                    local: ident.sym.to_string(),
+                    kind: if is_img { "image" } else { "arrayBuffer" }.to_string(),
+                })
+            });
+            if is_img {
+                self.await_items
+                    .push(AwaitItem::Img(ident.clone(), node.span));
             } else {
-                ident.sym = format!("_{}", ident.sym.as_str()).into();
-                self.await_items.push(AwaitItem::ArrayBuffer(ident.clone()));
+                self.await_items
+                    .push(AwaitItem::ArrayBuffer(ident.clone(), node.span));
             }
-            *node.src = Str::from([source, "url"].join("?"));
+            *node.src = Str {
+                span: node.src.span,
+                value: [source, "url"].join("?").into(),
+                raw: None,
+            };
         }
+        false
     }
 
-    fn visit_mut_module_items(&mut self, node: &mut Vec<ModuleItem>) {
-        node.visit_mut_children_with(self);
+    fn exit_module_items(&mut self, node: &mut Vec<ModuleItem>, ctx: &mut HookContext) {
+        let Some(span) = self.await_items.first().map(|item| match item {
+            AwaitItem::Img(_, span) | AwaitItem::ArrayBuffer(_, span) => *span,
+        }) else {
+            return;
+        };
 
-        let mut out: Vec<ModuleItem> = vec![];
+        let mut collector = IdentCollector::default();
+        node.visit_with(&mut collector);
 
-        while let Some(item) = self.await_items.pop() {
-            match item {
-                AwaitItem::Img(source) => {
-                    out.push(quote!(
+        // 按导入顺序同时开始，图片没有结果
+        let mut promises: Vec<Option<ExprOrSpread>> = vec![];
+        let mut results: Vec<Option<Pat>> = vec![];
+        for item in mem::take(&mut self.await_items) {
+            let (promise, result) = match item {
+                AwaitItem::Img(source, _) => (
+                    quote!(
                       "
-                      await new Promise((onload, onerror) => Object.assign(new Image, {src: $source, onload, onerror}))
-                      " as ModuleItem,
+                      new Promise((onload, onerror) => Object.assign(new Image, {src: $source, onload, onerror}))
+                      " as Expr,
                       source: Ident = source
-                    ));
+                    ),
+                    None,
+                ),
+                AwaitItem::ArrayBuffer(local, _) => {
+                    let source = Ident {
+                        sym: collector.unique(&local.sym),
+                        ..local.clone()
+                    };
+                    rename_import(node, &local, &source);
+                    (
+                        quote!(
+                            "
+                        fetch($source).then(e => e.arrayBuffer())
+                        " as Expr,
+                            source: Ident = source
+                        ),
+                        Some(local.into()),
+                    )
                 }
-                AwaitItem::ArrayBuffer(source) => {
-                    out.push(quote!(
-                        "
-                        const data = await fetch($source).then(e => e.arrayBuffer())
-                        " as ModuleItem,
-                        source: Ident = source
-                    ));
+            };
+            promises.push(Some(promise.into()));
+            results.push(result);
+        }
+        while results.last().is_some_and(|x| x.is_none()) {
+            results.pop();
+        }
+
+        // 只有一个时不需要 `Promise.all`
+        let (init, pat) = if promises.len() == 1 {
+            let promise = *promises.pop().flatten().unwrap().expr;
+            (
+                quote!("await $promise" as Expr, promise: Expr = promise),
+                results.pop().flatten(),
+            )
+        } else {
+            let promises: Expr = ArrayLit {
+                span: DUMMY_SP,
+                elems: promises,
+            }
+            .into();
+            let pat = (!results.is_empty()).then(|| {
+                ArrayPat {
+                    span: DUMMY_SP,
+                    elems: results,
+                    optional: false,
+                    type_ann: None,
                 }
+                .into()
+            });
+            (
+                quote!("await Promise.all($promises)" as Expr, promises: Expr = promises),
+                pat,
+            )
+        };
+
+        // 生成的语句使用第一个预加载导入语句的 span
+        let mut stmt: Stmt = match pat {
+            None => ExprStmt {
+                span,
+                expr: Box::new(init),
             }
-        }
+            .into(),
+            Some(name) => VarDecl {
+                span,
+                kind: VarDeclKind::Const,
+                decls: vec![VarDeclarator {
+                    span: DUMMY_SP,
+                    name,
+                    init: Some(Box::new(init)),
+                    definite: false,
+                }],
+                ..Default::default()
+            }
+            .into(),
+        };
+        ctx.visit(&mut stmt);
 
-        let index = node.partition_point(|x| x.is_module_decl());
+        // 导出语句可能已经使用了结果，插入到开头的导入语句之后
+        let index = node
+            .iter()
+            .position(|x| !matches!(x, ModuleItem::ModuleDecl(ModuleDecl::Import(_))))
+            .unwrap_or(node.len());
 
-        node.splice(index..index, out);
+        node.insert(index, stmt.into());
     }
 }
 
-pub fn preload_transform() -> impl VisitMut {
+/// 把 `?url` 导入语句的本地名称改为 `source`
+fn rename_import(items: &mut [ModuleItem], local: &Ident, source: &Ident) {
+    for item in items {
+        if let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item {
+            for specifier in import.specifiers.iter_mut() {
+                let ident = specifier.local_mut();
+                if ident.sym == local.sym && ident.ctxt == local.ctxt {
+                    ident.sym = source.sym.clone();
+                }
+            }
+        }
+    }
+}
+
+pub fn preload_hook() -> impl Hook {
     TransformVisitor::default()
 }
+
+pub fn preload_transform() -> impl VisitMut {
+    HookVisitor::new(vec![Box::new(preload_hook())])
+}
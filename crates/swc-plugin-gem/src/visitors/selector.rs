@@ -1,25 +1,23 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
-use swc_common::DUMMY_SP;
-use swc_core::ecma::visit::{noop_visit_mut_type, VisitMut, VisitMutWith};
+use swc_core::ecma::visit::VisitMut;
 use swc_ecma_ast::{TaggedTpl, Tpl, TplElement};
 
+use super::hook::{Hook, HookContext, HookVisitor};
+
 static COMMENT_REG: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(
-        r#"&((?:[^\s{\(\["']+|\([^()]*\)|\[[^\]]*\]|"[^"]*"|'[^']*')+)"#,
-    )
-    .unwrap()
+    Regex::new(r#"&((?:[^\s{\(\["']+|\([^()]*\)|\[[^\]]*\]|"[^"]*"|'[^']*')+)"#).unwrap()
 });
 
 fn trans_css_tpl(tpl: &Tpl) -> Tpl {
     Tpl {
-        span: DUMMY_SP,
+        span: tpl.span,
         exprs: tpl.exprs.clone(),
         quasis: tpl
             .quasis
             .iter()
             .map(|x| TplElement {
-                span: DUMMY_SP,
+                span: x.span,
                 tail: x.tail,
                 cooked: None,
                 raw: COMMENT_REG
@@ -33,12 +31,8 @@ fn trans_css_tpl(tpl: &Tpl) -> Tpl {
 #[derive(Default)]
 struct TransformVisitor {}
 
-impl VisitMut for TransformVisitor {
-    noop_visit_mut_type!();
-
-    fn visit_mut_tagged_tpl(&mut self, node: &mut TaggedTpl) {
-        node.visit_mut_children_with(self);
-
+impl Hook for TransformVisitor {
+    fn exit_tagged_tpl(&mut self, node: &mut TaggedTpl, _: &mut HookContext) {
         if let Some(ident) = node.tag.as_ident() {
             let tag_fn = ident.sym.as_str();
             if tag_fn == "css" {
@@ -48,6 +42,10 @@ impl VisitMut for TransformVisitor {
     }
 }
 
-pub fn selector_transform() -> impl VisitMut {
+pub fn selector_hook() -> impl Hook {
     TransformVisitor::default()
 }
+
+pub fn selector_transform() -> impl VisitMut {
+    HookVisitor::new(vec![Box::new(selector_hook())])
+}
@@ -1,6 +1,11 @@
-use std::path::PathBuf;
+use std::{env, fs, path::PathBuf};
 
-use swc_core::ecma::transforms::testing::test_fixture;
+use indexmap::IndexMap;
+use swc_common::Mark;
+use swc_core::ecma::transforms::{
+    base::resolver,
+    testing::{test_fixture, FixtureTestConfig},
+};
 use swc_ecma_parser::{Syntax, TsSyntax};
 use swc_ecma_visit::visit_mut_pass;
 use swc_plugin_gem::*;
@@ -20,24 +25,86 @@ fn fixture_auto_import(input: PathBuf) {
     test_fixture(
         get_syntax(),
         &|_| {
-            visit_mut_pass(import_transform(
-                AutoImport::CustomContent(AutoImportContent {
-                    extends: Some("gem".to_string()),
-                    members: Some(
-                        vec![(
-                            "test".to_string(),
-                            vec![MemberOrMemberAs::MemberAs([
-                                "name".to_string(),
-                                "alias".to_string(),
-                            ])],
-                        )]
-                        .into_iter()
-                        .collect(),
-                    ),
-                    elements: None,
-                }),
-                AutoImportDts::Src(false),
-            ))
+            let unresolved_mark = Mark::new();
+            (
+                resolver(unresolved_mark, Mark::new(), true),
+                visit_mut_pass(import_transform(
+                    AutoImport::CustomContent(AutoImportContent {
+                        extends: Some(AutoImportExtends::One("gem".to_string())),
+                        members: Some(
+                            vec![
+                                (
+                                    "test".to_string(),
+                                    vec![MemberOrMemberAs::MemberAs([
+                                        "name".to_string(),
+                                        "alias".to_string(),
+                                    ])],
+                                ),
+                                (
+                                    "icons/{:kebab:}".to_string(),
+                                    vec![MemberOrMemberAs::MemberAs([
+                                        "default".to_string(),
+                                        "Icon*".to_string(),
+                                    ])],
+                                ),
+                                (
+                                    "@mantou/gem".to_string(),
+                                    vec![MemberOrMemberAs::MemberAs([
+                                        "html".to_string(),
+                                        "tpl".to_string(),
+                                    ])],
+                                ),
+                                (
+                                    "@mantou/gem/elements/{:camel:}".to_string(),
+                                    vec![MemberOrMemberAs::Member("ColorPicker".to_string())],
+                                ),
+                            ]
+                            .into_iter()
+                            .collect(),
+                        ),
+                        types: None,
+                        elements: None,
+                        report_unknown_elements: None,
+                        registry: Some("tests/fixture/auto-import/registry/elements".to_string()),
+                        priority: None,
+                        exclude: None,
+                    }),
+                    AutoImportDts::Src(false),
+                    false,
+                    unresolved_mark,
+                    Some(input.to_string_lossy().to_string()),
+                    Some(IndexMap::from_iter([(
+                        ".tsx".to_string(),
+                        ".jsx".to_string(),
+                    )])),
+                )),
+            )
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+}
+
+#[fixture("tests/fixture/lazy-view/input.ts")]
+fn fixture_lazy_view(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.ts");
+
+    test_fixture(
+        get_syntax(),
+        &|_| {
+            let unresolved_mark = Mark::new();
+            (
+                resolver(unresolved_mark, Mark::new(), true),
+                visit_mut_pass(import_transform(
+                    AutoImport::Gem(true),
+                    AutoImportDts::Src(false),
+                    true,
+                    unresolved_mark,
+                    None,
+                    None,
+                )),
+            )
         },
         &input,
         &output,
@@ -85,15 +152,82 @@ fn fixture_selector(input: PathBuf) {
 }
 
 #[fixture("tests/fixture/path/input.ts")]
+#[fixture("tests/fixture/path-forms/input.ts")]
 fn fixture_path(input: PathBuf) {
     let output = input.parent().unwrap().join("output.ts");
+    let filename = input.to_string_lossy().to_string();
+
+    test_fixture(
+        get_syntax(),
+        &|_| visit_mut_pass(path_transform(Some(filename.clone()), Default::default())),
+        &input,
+        &output,
+        Default::default(),
+    );
+}
+
+#[fixture("tests/fixture/path-resolve/input.ts")]
+fn fixture_path_resolve(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.ts");
+
+    test_fixture(
+        get_syntax(),
+        &|_| {
+            visit_mut_pass(path_transform(
+                Some("tests/fixture/path-resolve/input.ts".to_string()),
+                ResolvePathOptions {
+                    tsconfig: Some("tests/fixture/path-resolve/tsconfig.json".to_string()),
+                    ..Default::default()
+                },
+            ))
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+}
+
+#[fixture("tests/fixture/path-strict/input.ts")]
+fn fixture_path_strict(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.ts");
 
     test_fixture(
         get_syntax(),
         &|_| {
-            visit_mut_pass(path_transform(Some(
-                "tests/fixture/path/input.ts".to_string(),
-            )))
+            visit_mut_pass(path_transform(
+                Some("tests/fixture/path-strict/input.ts".to_string()),
+                ResolvePathOptions {
+                    strict: true,
+                    allow: Some(vec!["?url".to_string()]),
+                    ..Default::default()
+                },
+            ))
+        },
+        &input,
+        &output,
+        FixtureTestConfig {
+            allow_error: true,
+            ..Default::default()
+        },
+    );
+}
+
+#[fixture("tests/fixture/path-public/input.ts")]
+fn fixture_path_public(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.ts");
+
+    test_fixture(
+        get_syntax(),
+        &|_| {
+            visit_mut_pass(path_transform(
+                Some("tests/fixture/path-public/input.ts".to_string()),
+                ResolvePathOptions {
+                    public_path: Some("/static/app".to_string()),
+                    public_root: Some("tests/fixture/path-public/public".to_string()),
+                    hash: true,
+                    ..Default::default()
+                },
+            ))
         },
         &input,
         &output,
@@ -101,7 +235,108 @@ fn fixture_path(input: PathBuf) {
     );
 }
 
-#[fixture("tests/fixture/preload/input.ts")]
+#[fixture("tests/fixture/path-ts-esm/input.ts")]
+fn fixture_path_ts_esm(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.ts");
+
+    test_fixture(
+        get_syntax(),
+        &|_| {
+            visit_mut_pass(path_transform(
+                Some("tests/fixture/path-ts-esm/input.ts".to_string()),
+                ResolvePathOptions {
+                    strict: true,
+                    ..Default::default()
+                },
+            ))
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+}
+
+#[fixture("tests/fixture/path-import-map/input.ts")]
+fn fixture_path_import_map(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.ts");
+
+    test_fixture(
+        get_syntax(),
+        &|_| {
+            visit_mut_pass(path_transform(
+                Some("tests/fixture/path-import-map/input.ts".to_string()),
+                ResolvePathOptions {
+                    import_map: Some("tests/fixture/path-import-map/import-map.json".to_string()),
+                    ..Default::default()
+                },
+            ))
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+}
+
+#[fixture("tests/fixture/path-emit-import-map/input.ts")]
+fn fixture_path_emit_import_map(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.ts");
+    let emit = env::temp_dir().join(format!("gem-import-map-{}.json", std::process::id()));
+
+    test_fixture(
+        get_syntax(),
+        &|_| {
+            visit_mut_pass(path_transform(
+                Some("tests/fixture/path-emit-import-map/input.ts".to_string()),
+                ResolvePathOptions {
+                    import_map: Some("tests/fixture/path-import-map/import-map.json".to_string()),
+                    emit_import_map: Some(emit.to_string_lossy().to_string()),
+                    tsconfig: Some("tests/fixture/path-emit-import-map/tsconfig.json".to_string()),
+                    ..Default::default()
+                },
+            ))
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+
+    let import_map = fs::read_to_string(&emit).unwrap();
+    fs::remove_file(&emit).unwrap();
+    let parts = import_map_parts_dir(&emit);
+    fs::remove_dir_all(&parts).unwrap();
+    for dir in parts.ancestors().skip(1).take(4) {
+        if fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+    assert_eq!(
+        serde_json::from_str::<ImportMap>(&import_map)
+            .unwrap()
+            .imports
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "@mantou/gem".to_string(),
+                "/vendor/gem/index.js".to_string()
+            ),
+            (
+                "@mantou/gem/elements/link.js".to_string(),
+                "/vendor/gem/elements/link.js".to_string()
+            ),
+            (
+                "@mantou/gem/lib/store.js".to_string(),
+                "/vendor/gem/lib/store.js".to_string()
+            ),
+            (
+                "local-pkg".to_string(),
+                "/tests/fixture/path-emit-import-map/node_modules/local-pkg/index.js".to_string()
+            ),
+        ]
+    );
+}
+
+#[fixture("tests/fixture/preload/**/input.ts")]
 fn fixture_preload(input: PathBuf) {
     let output = input.parent().unwrap().join("output.ts");
 
@@ -114,6 +349,22 @@ fn fixture_preload(input: PathBuf) {
     );
 }
 
+#[fixture("tests/fixture/preload-error/input.ts")]
+fn fixture_preload_error(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.ts");
+
+    test_fixture(
+        get_syntax(),
+        &|_| visit_mut_pass(preload_transform()),
+        &input,
+        &output,
+        FixtureTestConfig {
+            allow_error: true,
+            ..Default::default()
+        },
+    );
+}
+
 #[fixture("tests/fixture/hmr/**/input.ts")]
 fn fixture_hmr(input: PathBuf) {
     let output = input.parent().unwrap().join("output.ts");
@@ -131,3 +382,35 @@ fn fixture_hmr(input: PathBuf) {
         Default::default(),
     );
 }
+
+#[fixture("tests/fixture/combined/input.ts")]
+fn fixture_combined(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.ts");
+    let config = PluginConfig {
+        style_minify: true,
+        auto_import: AutoImport::Gem(true),
+        preload: true,
+        hmr: HmrConfig::Enabled(true),
+        selector_compatible: true,
+        lazy_view: true,
+        ..Default::default()
+    };
+
+    test_fixture(
+        get_syntax(),
+        &|_| {
+            let unresolved_mark = Mark::new();
+            (
+                resolver(unresolved_mark, Mark::new(), true),
+                visit_mut_pass(gem_transform(
+                    &config,
+                    Some("combined.ts".to_string()),
+                    unresolved_mark,
+                )),
+            )
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+}
@@ -0,0 +1,2 @@
+// @ts-nocheck
+export const content = tpl`<div>${html`${css``}`}</div>`;
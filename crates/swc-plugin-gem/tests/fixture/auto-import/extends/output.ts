@@ -0,0 +1,3 @@
+// @ts-nocheck
+import { html as tpl, html, css } from "@mantou/gem";
+export const content = tpl`<div>${html`${css``}`}</div>`;
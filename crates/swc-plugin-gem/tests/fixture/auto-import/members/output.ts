@@ -1,7 +1,6 @@
 // @ts-nocheck
-import { css, adoptedStyle, customElement, attribute, emitter, template, html, styleMap } from "@mantou/gem";
 import { name as alias } from "test";
-import { render, Emitter, GemElement } from '@mantou/gem';
+import { render, Emitter, GemElement, css, adoptedStyle, customElement, attribute, emitter, template, html, styleMap } from '@mantou/gem';
 const style = css``;
 @adoptedStyle(style)
 @customElement('my-element')
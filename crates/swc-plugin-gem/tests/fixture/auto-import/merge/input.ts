@@ -0,0 +1,8 @@
+// @ts-nocheck
+import * as gem from '@mantou/gem';
+import type { StyleObject } from '@mantou/gem';
+import '@mantou/gem/elements/route';
+import { name } from 'test';
+
+const style: Store = css``;
+html`<gem-route></gem-route><gem-link></gem-link>${alias}${style}${gem}${name}`;
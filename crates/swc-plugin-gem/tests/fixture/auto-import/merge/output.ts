@@ -0,0 +1,9 @@
+// @ts-nocheck
+import { css, html } from "@mantou/gem";
+import "@mantou/gem/elements/link";
+import * as gem from '@mantou/gem';
+import type { StyleObject, Store } from '@mantou/gem';
+import '@mantou/gem/elements/route';
+import { name, name as alias } from 'test';
+const style: Store = css``;
+html`<gem-route></gem-route><gem-link></gem-link>${alias}${style}${gem}${name}`;
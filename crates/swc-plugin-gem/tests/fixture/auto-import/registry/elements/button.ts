@@ -0,0 +1,3 @@
+// @ts-nocheck
+@customElement('app-button')
+export class AppButton extends GemElement {}
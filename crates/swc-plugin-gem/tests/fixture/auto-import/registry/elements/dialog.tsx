@@ -0,0 +1,3 @@
+// @ts-nocheck
+@customElement('app-dialog')
+export class AppDialog extends GemElement {}
@@ -0,0 +1,7 @@
+// @ts-nocheck
+@customElement('app-card')
+export class AppCard extends GemElement {
+  render() {
+    return html`<app-button></app-button>`;
+  }
+}
@@ -0,0 +1,14 @@
+// @ts-nocheck
+
+@customElement('app-root')
+export class AppRoot extends GemElement {
+  render() {
+    return html`
+      <app-root></app-root>
+      <app-button></app-button>
+      <app-card></app-card>
+      <app-dialog></app-dialog>
+      <app-unknown></app-unknown>
+    `;
+  }
+}
@@ -0,0 +1,17 @@
+// @ts-nocheck
+import { customElement, html, GemElement } from "@mantou/gem";
+import "./elements/button.js";
+import "./elements/nested/card.js";
+import "./elements/dialog.jsx";
+@customElement('app-root')
+export class AppRoot extends GemElement {
+    render() {
+        return html`
+      <app-root></app-root>
+      <app-button></app-button>
+      <app-card></app-card>
+      <app-dialog></app-dialog>
+      <app-unknown></app-unknown>
+    `;
+    }
+}
@@ -0,0 +1,8 @@
+// @ts-nocheck
+import { html } from '@mantou/gem';
+
+html`<dy-avatar${attrs}></dy-avatar><!-- <dy-tooltip> --><div title="<dy-badge>"></div>`;
+svg`<foreignObject><gem-title></gem-title></foreignObject><font-face></font-face>`;
+document.createElement('dy-input');
+await customElements.whenDefined('gem-link');
+document.createElement('div');
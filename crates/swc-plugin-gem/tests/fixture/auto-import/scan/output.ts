@@ -0,0 +1,11 @@
+// @ts-nocheck
+import "duoyun-ui/elements/avatar";
+import "@mantou/gem/elements/title";
+import "duoyun-ui/elements/input";
+import "@mantou/gem/elements/link";
+import { html, svg } from '@mantou/gem';
+html`<dy-avatar${attrs}></dy-avatar><!-- <dy-tooltip> --><div title="<dy-badge>"></div>`;
+svg`<foreignObject><gem-title></gem-title></foreignObject><font-face></font-face>`;
+document.createElement('dy-input');
+await customElements.whenDefined('gem-link');
+document.createElement('div');
@@ -0,0 +1,17 @@
+// @ts-nocheck
+import { render } from '@mantou/gem';
+
+function local() {
+  const css = (s) => s;
+  return css``;
+}
+function param(html, { styleMap }) {
+  return html`${styleMap}`;
+}
+try {
+  render();
+} catch (classMap) {
+  classMap();
+}
+function generic<Store>(store: Store) {}
+const style = css``;
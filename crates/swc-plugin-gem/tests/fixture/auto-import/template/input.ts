@@ -0,0 +1,4 @@
+// @ts-nocheck
+import { html } from '@mantou/gem';
+
+html`${IconArrowLeft}${IconHTMLElement}${ColorPicker}${IconArrowLeft}`;
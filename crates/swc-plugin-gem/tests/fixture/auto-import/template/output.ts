@@ -0,0 +1,6 @@
+// @ts-nocheck
+import { default as IconArrowLeft } from "icons/arrow-left";
+import { default as IconHTMLElement } from "icons/html-element";
+import { ColorPicker } from "@mantou/gem/elements/colorPicker";
+import { html } from '@mantou/gem';
+html`${IconArrowLeft}${IconHTMLElement}${ColorPicker}${IconArrowLeft}`;
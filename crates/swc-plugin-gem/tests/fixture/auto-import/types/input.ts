@@ -0,0 +1,13 @@
+// @ts-nocheck
+import { html } from '@mantou/gem';
+interface StyleObject {}
+
+@customElement('my-element')
+class MyElement extends GemElement<Store> implements Partial<Emitter> {
+  @emitter open: Emitter<null>;
+  style: StyleObject;
+
+  get store(): Store<{ a: number }> {
+    return createStore({ a: 1 });
+  }
+}
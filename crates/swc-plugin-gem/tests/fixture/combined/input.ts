@@ -0,0 +1,19 @@
+// @ts-nocheck
+import img from './a.png?preload';
+@customElement('my-mix')
+class Mix extends GemElement {
+  @memo((i) => [i.#a])
+  get #b() { return this.#a + css`a { color: red; }`; }
+  #a = 1;
+  @effect((i) => [i.x]) x = () => html`<gem-link></gem-link>`;
+  render() {
+    const routes = [{ pattern: '/', content: html`<gem-title></gem-title>` }];
+    return html`<div>${styleMap({})}</div> <gem-route .routes=${routes}></gem-route>`;
+  }
+}
+class Outer {
+  #x = 1;
+  m() { return class Inner { #y; f() { return this.#y; } }; }
+  constructor() { this.#x = 2; }
+}
+const style = createCSSSheet({ a: `color: red;  ` });
@@ -0,0 +1,124 @@
+// @ts-nocheck
+import { customElement, css, html, effect, styleMap, memo, GemElement } from "@mantou/gem";
+import "@mantou/gem/elements/link";
+import "@mantou/gem/elements/route";
+import img from "./a.png?url";
+await new Promise((onload, onerror)=>Object.assign(new Image, {
+        src: img,
+        onload,
+        onerror
+    }));
+@customElement('my-mix')
+@(window._hmrRegisterClass ? _hmrRegisterClass("my-mix") : Function.prototype)
+class Mix extends GemElement {
+    _hmr_private_my_mix__b_get() {
+        return this._private_my_mix_a + css`a{color:red;}`;
+    }
+    get _private_my_mix__b() {
+        return this._hmr_private_my_mix__b_get.bind(this)();
+    }
+    _private_my_mix_a = 1;
+    _hmr_public_my_mix_x() {
+        return html`<gem-link></gem-link>`;
+    }
+    @effect((i)=>Mix._dep_fn_0(i))
+    x = (...args)=>{
+        return this._hmr_public_my_mix_x.bind(this)(...args);
+    };
+    _hmr_public_my_mix_render() {
+        const routes = [
+            {
+                pattern: '/',
+                getContent: async ()=>{
+                    await import("@mantou/gem/elements/title");
+                    return html`<gem-title></gem-title>`;
+                }
+            }
+        ];
+        return html`<div>${styleMap({})}</div> <gem-route .routes=${routes}></gem-route>`;
+    }
+    render(...args) {
+        return this._hmr_public_my_mix_render.bind(this)(...args);
+    }
+    _hmr_private_my_mix___b() {
+        this._private_my_mix_b = this._private_my_mix__b;
+    }
+    @memo((i)=>[
+            i._private_my_mix_a
+        ])
+    _private_my_mix___b(...args) {
+        return this._hmr_private_my_mix___b.bind(this)(...args);
+    }
+    _private_my_mix_b;
+    static _hmr_public_my_mix__dep_fn_0(i) {
+        return [
+            i.x
+        ];
+    }
+    static _dep_fn_0 = (...args)=>{
+        return this._hmr_public_my_mix__dep_fn_0.bind(this)(...args);
+    };
+    static{
+        this._defined_fields_ = [
+            [
+                "_private_my_mix_b",
+                "other",
+                false
+            ],
+            [
+                "_private_my_mix_a",
+                "other",
+                false
+            ]
+        ];
+    }
+}
+@(window._hmrRegisterClass ? _hmrRegisterClass("hash_b9038b5a8f5fa093") : Function.prototype)
+class Outer {
+    _private_hash_b9038b5a8f5fa093_x = 1;
+    _hmr_public_hash_b9038b5a8f5fa093_m() {
+        return @(window._hmrRegisterClass ? _hmrRegisterClass("hash_7869a60bcaf4a8e3") : Function.prototype)
+        class Inner {
+            _private_hash_7869a60bcaf4a8e3_y;
+            _hmr_public_hash_7869a60bcaf4a8e3_f() {
+                return this._private_hash_7869a60bcaf4a8e3_y;
+            }
+            f(...args) {
+                return this._hmr_public_hash_7869a60bcaf4a8e3_f.bind(this)(...args);
+            }
+            static{
+                this._defined_fields_ = [
+                    [
+                        "_private_hash_7869a60bcaf4a8e3_y",
+                        "other",
+                        false
+                    ]
+                ];
+            }
+        };
+    }
+    m(...args) {
+        return this._hmr_public_hash_b9038b5a8f5fa093_m.bind(this)(...args);
+    }
+    _hmr_public_hash_b9038b5a8f5fa093_constructor() {
+        this._private_hash_b9038b5a8f5fa093_x = 2;
+    }
+    constructor(...args){
+        this._hmr_public_hash_b9038b5a8f5fa093_constructor.bind(this)(...args);
+    }
+    static{
+        this._defined_fields_ = [
+            [
+                "_private_hash_b9038b5a8f5fa093_x",
+                "other",
+                false
+            ]
+        ];
+    }
+}
+const style = createCSSSheet({
+    a: `color:red;`
+});
+if (import.meta.webpackHot) {
+    import.meta.webpackHot.accept();
+}
@@ -0,0 +1,19 @@
+// @ts-nocheck
+export const routes = {
+  home: {
+    pattern: '/',
+    content: html`<dy-pat-console></dy-pat-console>`,
+  },
+  items: {
+    pattern: '/items',
+    getContent() {
+      return html`<dy-pat-table></dy-pat-table><dy-pat-form></dy-pat-form>`;
+    },
+  },
+  item: {
+    pattern: '/items/:id',
+    getContent: (params) => html`<dy-pat-item .id=${params.id}></dy-pat-item>`,
+  },
+};
+
+render(html`<dy-light-route .routes=${routes}></dy-light-route>`, document.body);
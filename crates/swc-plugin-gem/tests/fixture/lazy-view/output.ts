@@ -0,0 +1,30 @@
+// @ts-nocheck
+import { html } from "@mantou/gem";
+import "duoyun-ui/elements/route";
+export const routes = {
+    home: {
+        pattern: '/',
+        getContent: async ()=>{
+            await import("duoyun-ui/patterns/console");
+            return html`<dy-pat-console></dy-pat-console>`;
+        }
+    },
+    items: {
+        pattern: '/items',
+        async getContent () {
+            await Promise.all([
+                import("duoyun-ui/patterns/table"),
+                import("duoyun-ui/patterns/form")
+            ]);
+            return html`<dy-pat-table></dy-pat-table><dy-pat-form></dy-pat-form>`;
+        }
+    },
+    item: {
+        pattern: '/items/:id',
+        getContent: async (params)=>{
+            await import("duoyun-ui/patterns/item");
+            return html`<dy-pat-item .id=${params.id}></dy-pat-item>`;
+        }
+    }
+};
+render(html`<dy-light-route .routes=${routes}></dy-light-route>`, document.body);
@@ -0,0 +1,9 @@
+// @ts-nocheck
+import { html } from '@mantou/gem';
+import { createStore } from '@mantou/gem/lib/store.js';
+import 'lit';
+import 'local-pkg';
+import { util } from '@/util';
+import '../path/output';
+
+export const load = () => import('@mantou/gem/elements/link.js');
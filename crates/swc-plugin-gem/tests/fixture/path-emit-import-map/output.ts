@@ -0,0 +1,8 @@
+// @ts-nocheck
+import { html } from "@mantou/gem";
+import { createStore } from "@mantou/gem/lib/store.js";
+import "lit";
+import "local-pkg";
+import { util } from "./src/util.js";
+import "../path/output.js";
+export const load = ()=>import("@mantou/gem/elements/link.js");
@@ -0,0 +1,12 @@
+// @ts-nocheck
+export { a } from './a';
+export * from './b';
+export * as ns from './b';
+export * from './c';
+export * from './d';
+export * from './types.tsx-utils/x';
+
+export const url = import.meta.resolve('./a');
+export const worker = new Worker(new URL('./worker', import.meta.url), { type: 'module' });
+export const asset = new URL('b', import.meta.url);
+export const load = () => Promise.all([import('./a'), import('./b', { with: {} })]);
@@ -0,0 +1,18 @@
+// @ts-nocheck
+export { a } from "./a.js";
+export * from "./b.js";
+export * as ns from "./b.js";
+export * from "./c.js";
+export * from "./d.mjs";
+export * from "./types.tsx-utils/x.js";
+export const url = import.meta.resolve("./a.js");
+export const worker = new Worker(new URL("./worker.js", import.meta.url), {
+    type: 'module'
+});
+export const asset = new URL('b', import.meta.url);
+export const load = ()=>Promise.all([
+        import("./a.js"),
+        import("./b.js", {
+            with: {}
+        })
+    ]);
@@ -0,0 +1,6 @@
+// @ts-nocheck
+import { html } from "/vendor/gem/index.js";
+import { createStore } from "/vendor/gem/lib/store.js";
+import "lit";
+import "../path/output.js";
+export const load = ()=>import("/vendor/gem/elements/link.js");
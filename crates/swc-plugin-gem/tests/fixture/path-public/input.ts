@@ -0,0 +1,6 @@
+import { a } from './public/src/utils';
+import { outside } from './lib/outside';
+import 'https://esm.sh/lit';
+
+const worker = new URL('./public/src/utils', import.meta.url);
+export const lazy = import('./public/src/utils');
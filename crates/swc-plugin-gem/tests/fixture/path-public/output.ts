@@ -0,0 +1,5 @@
+import { a } from "/static/app/src/utils.js?v=9324a58a";
+import { outside } from "./lib/outside.js?v=6be399be";
+import "https://esm.sh/lit";
+const worker = new URL("/static/app/src/utils.js?v=9324a58a", import.meta.url);
+export const lazy = import("/static/app/src/utils.js?v=9324a58a");
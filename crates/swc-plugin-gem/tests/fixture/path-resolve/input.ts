@@ -0,0 +1,6 @@
+// @ts-nocheck
+import '#internal/a';
+import 'pkg';
+import 'pkg/sub/x';
+import '@/util';
+import './src/util';
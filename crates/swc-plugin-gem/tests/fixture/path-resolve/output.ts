@@ -0,0 +1,6 @@
+// @ts-nocheck
+import "./internal/a.browser.js";
+import "./node_modules/pkg/browser.js";
+import "./node_modules/pkg/lib/x.js";
+import "./src/util.js";
+import "./src/util.js";
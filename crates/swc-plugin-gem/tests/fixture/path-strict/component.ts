@@ -0,0 +1 @@
+export const component = 1;
@@ -0,0 +1,10 @@
+// @ts-nocheck
+import "./compnent";
+import "./component.js";
+import "./logo.svg?url";
+import "./missing.js";
+export * from "../path-strict/componet";
+import type { Props } from './types';
+import { type State } from './types';
+export type { Props as P } from './types';
+import { type Props as Q, component } from "./component.js";
@@ -0,0 +1,2 @@
+export interface Props {}
+export type State = string;
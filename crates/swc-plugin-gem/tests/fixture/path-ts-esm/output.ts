@@ -0,0 +1,5 @@
+// @ts-nocheck
+import { component } from "./component.js";
+import { view } from "./view.js";
+import { view as view2 } from "./view.js";
+export { worker } from "./worker.mjs";
@@ -0,0 +1 @@
+export const view = 1;
@@ -0,0 +1 @@
+export const worker = 1;
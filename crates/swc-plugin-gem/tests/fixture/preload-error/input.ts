@@ -0,0 +1,4 @@
+// @ts-nocheck
+import { src } from 'xxxx.png?preload';
+import 'xxxx.data?preload';
+console.log(1);
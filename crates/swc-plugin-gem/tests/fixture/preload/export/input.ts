@@ -0,0 +1,5 @@
+// @ts-nocheck
+import data from 'xxxx.data?preload';
+export default data;
+export const n = data.byteLength;
+console.log(n);
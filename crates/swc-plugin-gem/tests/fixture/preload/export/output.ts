@@ -0,0 +1,6 @@
+// @ts-nocheck
+import _data from "xxxx.data?url";
+const data = await fetch(_data).then((e)=>e.arrayBuffer());
+export default data;
+export const n = data.byteLength;
+console.log(n);
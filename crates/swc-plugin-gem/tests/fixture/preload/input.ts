@@ -1,4 +1,7 @@
 // @ts-nocheck
 import src from 'xxxx.png?preload';
 import data from 'xxxx.data?preload';
-console.log(1);
+import icon from 'icon.svg?preload';
+import wasm from 'module.wasm?preload';
+const _data = 1;
+console.log(1, _data);
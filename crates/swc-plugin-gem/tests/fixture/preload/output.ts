@@ -1,10 +1,21 @@
 // @ts-nocheck
 import src from "xxxx.png?url";
-import _data from "xxxx.data?url";
-const data = await fetch(_data).then((e)=>e.arrayBuffer());
-await new Promise((onload, onerror)=>Object.assign(new Image, {
-        src: src,
-        onload,
-        onerror
-    }));
-console.log(1);
+import _data1 from "xxxx.data?url";
+import icon from "icon.svg?url";
+import _wasm from "module.wasm?url";
+const [, data, , wasm] = await Promise.all([
+    new Promise((onload, onerror)=>Object.assign(new Image, {
+            src: src,
+            onload,
+            onerror
+        })),
+    fetch(_data1).then((e)=>e.arrayBuffer()),
+    new Promise((onload, onerror)=>Object.assign(new Image, {
+            src: icon,
+            onload,
+            onerror
+        })),
+    fetch(_wasm).then((e)=>e.arrayBuffer())
+]);
+const _data = 1;
+console.log(1, _data);
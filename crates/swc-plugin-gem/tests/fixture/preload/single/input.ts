@@ -0,0 +1,3 @@
+// @ts-nocheck
+import data from 'xxxx.data?preload';
+console.log(data);
@@ -0,0 +1,4 @@
+// @ts-nocheck
+import _data from "xxxx.data?url";
+const data = await fetch(_data).then((e)=>e.arrayBuffer());
+console.log(data);
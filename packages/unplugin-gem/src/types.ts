@@ -4,9 +4,36 @@
 export type AutoImportConfig =
   | boolean
   | {
-      extends?: 'gem';
+      /**
+       * `gem`, a config file path or a package, or an array of them
+       */
+      extends?: string | string[];
       members?: Record<string, string[]>;
+      /**
+       * Only used in type positions, imported with `import type`,
+       * e.g. `{ 'my-package': ['Store<T = any>'] }`
+       */
+      types?: Record<string, string[]>;
       elements?: Record<string, Record<string, string>>;
+      /**
+       * Packages used first when they provide the same member
+       */
+      priority?: string[];
+      /**
+       * Members (and types) not auto imported, only for this config and its `extends`
+       */
+      exclude?: string[];
+      /**
+       * Directory scanned for `@customElement` classes, or a JSON file of tag -> file
+       * (paths relative to the JSON file), relative to cwd.
+       * Elements are imported with relative paths and `resolvePath.extensions`
+       */
+      registry?: string;
+      /**
+       * Warn on custom elements that no `elements` rule covers
+       * @default false
+       */
+      reportUnknownElements?: boolean;
     };
 
 /**
@@ -44,6 +71,76 @@ export interface HmrOptions {
   exclude?: string | RegExp | Array<string | RegExp>;
 }
 
+/**
+ * Import map options for `resolvePath`, paths are relative to cwd
+ */
+export interface ResolvePathOptions {
+  /**
+   * Import map JSON, bare specifiers are rewritten with its `imports`
+   * (exact or longest `/` prefix, `scopes` are not supported)
+   */
+  importMap?: string;
+
+  /**
+   * Keep bare specifiers and write the ones used by all transformed files to this import map JSON,
+   * values come from `importMap` or `node_modules` (`/node_modules/...`),
+   * entries of each file are cached in `node_modules/.cache/swc-plugin-gem`
+   */
+  emitImportMap?: string;
+
+  /**
+   * package.json `exports` / `imports` conditions, `default` always matches
+   * @default ['browser', 'import']
+   */
+  conditions?: string[];
+
+  /**
+   * tsconfig used for `paths` / `baseUrl`, `extends` is not followed,
+   * aliases are resolved before `importMap` / `emitImportMap`
+   * @default 'tsconfig.json'
+   */
+  tsconfig?: string;
+
+  /**
+   * Rewrite the final extension of resolved files, merged into the default.
+   * TS ESM specifiers (`./x.js`, `./x.mjs`, `./x.cjs`) also find their TS sources
+   * @default { '.ts': '.js', '.tsx': '.js', '.mts': '.mjs', '.cts': '.cjs' }
+   */
+  extensions?: Record<string, string>;
+
+  /**
+   * Error on relative or absolute specifiers that cannot be resolved
+   * @default false
+   */
+  strict?: boolean;
+
+  /**
+   * Specifiers containing one of these are not checked by `strict`
+   * @example ['?url']
+   */
+  allow?: string[];
+
+  /**
+   * Resolved files under `publicRoot` become absolute URLs under this base
+   * @example '/static/app/'
+   */
+  publicPath?: string;
+
+  /**
+   * Directory served at `publicPath`
+   * @default cwd
+   */
+  publicRoot?: string;
+
+  /**
+   * Append `?v=<hash>` computed from the resolved file content.
+   * Not transitive: when `b.ts` changes, `a.js` imports the new `b.js?v=...` but keeps its own URL,
+   * so immutable caching is only safe for files that import nothing
+   * @default false
+   */
+  hash?: boolean;
+}
+
 /**
  * Auto import .d.ts generation
  */
@@ -76,7 +173,7 @@ export interface UnpluginGemOptions {
    * ```ts
    * {
    *   autoImport: {
-   *     extends: 'gem',
+   *     extends: ['gem', './auto-import.json'],
    *     members: {
    *       'my-package': ['myFunction']
    *     }
@@ -102,7 +199,7 @@ export interface UnpluginGemOptions {
    * Resolve import paths to full ESM paths
    * @default false
    */
-  resolvePath?: boolean;
+  resolvePath?: boolean | ResolvePathOptions;
 
   /**
    * Enable resource preloading with ?preload query